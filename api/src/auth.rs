@@ -0,0 +1,161 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
+};
+use entity::sea_orm_active_enums::Role;
+use entity::user as User;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use sea_orm::{entity::*, DatabaseConnection};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+use super::error::ApiError;
+
+/// Claims expected in the bearer token sent by the frontend.
+///
+/// `sub` holds the id of the `user` row the token was issued for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+}
+
+/// An authenticated user, resolved from the `Authorization: Bearer <jwt>` header.
+///
+/// Adding this extractor to a handler rejects anonymous requests with a 401 and
+/// gives the handler the `user` row the token belongs to, so identity fields never
+/// have to be trusted from the request body.
+#[derive(Clone, Debug)]
+pub struct AuthUser(pub User::Model);
+
+/// The key bearer tokens are verified with, built from the configured JWT secret.
+///
+/// Without a secret every authenticated request is answered with a 500, so a
+/// misconfigured server never accepts unsigned tokens.
+#[derive(Clone, Default)]
+pub struct JwtKey(Option<DecodingKey>);
+
+impl JwtKey {
+    /// Builds the key from an HS256 shared secret, or an unusable key if `secret` is `None`.
+    pub fn new(secret: Option<&str>) -> Self {
+        JwtKey(secret.map(|secret| DecodingKey::from_secret(secret.as_bytes())))
+    }
+
+    /// Verifies `token` and returns its claims.
+    ///
+    /// Returns `None` if the signature or expiry is invalid, or if no secret is configured.
+    pub(crate) fn verify(&self, token: &str) -> Option<Claims> {
+        let key = self.0.as_ref()?;
+        decode::<Claims>(token, key, &Validation::new(Algorithm::HS256))
+            .ok()
+            .map(|data| data.claims)
+    }
+}
+
+/// The token in an `Authorization: Bearer <jwt>` header, if there is one.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    DatabaseConnection: FromRef<S>,
+    JwtKey: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token =
+            bearer_token(&parts.headers).ok_or_else(|| unauthorized("missing bearer token"))?;
+
+        let key = JwtKey::from_ref(state);
+        if key.0.is_none() {
+            tracing::error!("JWT_SECRET is not set, rejecting authenticated request");
+            return Err(ApiError::Internal(
+                "authentication is not configured".to_string(),
+            ));
+        }
+
+        let claims = key
+            .verify(token)
+            .ok_or_else(|| unauthorized("invalid token"))?;
+        let id: i32 = claims
+            .sub
+            .parse()
+            .map_err(|_| unauthorized("invalid token subject"))?;
+
+        let conn = DatabaseConnection::from_ref(state);
+        let user = User::Entity::find_by_id(id)
+            .one(&conn)
+            .await?
+            .ok_or_else(|| unauthorized("unknown user"))?;
+
+        Ok(AuthUser(user))
+    }
+}
+
+/// A role that [`RequireRole`] can check for.
+pub trait RoleGuard {
+    /// The least privileged role that passes the guard.
+    const ROLE: Role;
+}
+
+/// Marker for handlers restricted to admins.
+pub struct Admin;
+
+impl RoleGuard for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Marker for handlers restricted to moderators and admins.
+pub struct Moderator;
+
+impl RoleGuard for Moderator {
+    const ROLE: Role = Role::Moderator;
+}
+
+/// An authenticated user whose role is at least `R::ROLE`.
+///
+/// Anonymous requests are rejected with a 401 like [`AuthUser`], and users with
+/// a lower role are rejected with a 403.
+pub struct RequireRole<R>(pub User::Model, PhantomData<R>);
+
+/// An authenticated user that is allowed to use the `/api/admin` routes.
+pub type AdminUser = RequireRole<Admin>;
+
+#[async_trait]
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    DatabaseConnection: FromRef<S>,
+    JwtKey: FromRef<S>,
+    S: Send + Sync,
+    R: RoleGuard,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AuthUser(user) = AuthUser::from_request_parts(parts, state).await?;
+        if user.role < R::ROLE {
+            return Err(ApiError::Forbidden(format!(
+                "{} access required",
+                R::ROLE.to_value()
+            )));
+        }
+        Ok(RequireRole(user, PhantomData))
+    }
+}
+
+/// Returns true if `user` may edit or remove content owned by other users.
+pub fn can_moderate(user: &User::Model) -> bool {
+    user.role >= Role::Moderator
+}
+
+fn unauthorized(msg: &str) -> ApiError {
+    ApiError::Unauthorized(msg.to_string())
+}
@@ -1,33 +1,50 @@
-use super::internal_error;
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-};
+use super::auth::AdminUser;
+use super::error::{ApiError, ErrorBody};
+use super::extract::{FormOrJson, Path, Query};
+use super::pagination::{paginate, Paginated, Pagination};
+use axum::{extract::State, http::StatusCode, response::Json};
 use entity::brewer as Brewer;
-use sea_orm::{entity::*, DatabaseConnection};
+use entity::recipe as Recipe;
+use sea_orm::{entity::*, query::*, DatabaseConnection};
 
 /// Retrieves a list of brewers from the database and returns them as a JSON response.
 ///
 /// # Parameters:
 /// - `State(conn)`: Extracted state which contains the `DatabaseConnection` to be used for database queries.
+/// - `Query(page)`: The pagination parameters, see [`Pagination`].
 ///
 /// # Returns:
-/// - `Result<Json<Vec<Brewer::Model>>, (StatusCode, String)>`:
-///   - On success: a `Json` response containing a vector of `Brewer::Model`.
-///   - On failure: a tuple containing an HTTP status code and a string message describing the error.
+/// - `Result<Json<Paginated<Brewer::Model>>, ApiError>`:
+///   - On success: a `Json` response containing one page of `Brewer::Model`.
+///   - On failure: an `ApiError` describing the error, rendered as a JSON error response.
 ///
 /// # Errors:
-/// This function will return an error if the database query fails. The error will be converted
-/// into an internal server error represented by an HTTP status code of 500 and an error message.
+/// This function will return `ApiError::Internal` if the database query fails, which is rendered
+/// as a `500` JSON error response.
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/brewers",
+    tag = "brewers",
+    summary = "List brewers",
+    params(Pagination),
+    responses(
+        (status = 200, description = "One page of brewers", body = Paginated<Brewer::Model>),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_brewers(
     State(conn): State<DatabaseConnection>,
-) -> Result<Json<Vec<Brewer::Model>>, (StatusCode, String)> {
-    let brewers = Brewer::Entity::find()
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+    Query(page): Query<Pagination>,
+) -> Result<Json<Paginated<Brewer::Model>>, ApiError> {
+    let brewers = paginate(
+        &conn,
+        Brewer::Entity::find(),
+        Brewer::Column::Id,
+        &page,
+        |brewer: &Brewer::Model| brewer.id,
+    )
+    .await?;
     Ok(Json(brewers))
 }
 
@@ -41,20 +58,177 @@ pub async fn get_brewers(
 /// # Returns
 ///
 /// This function returns a `Result`:
-/// - `Ok(Json<Option<Brewer::Model>>)` - If the entity is found successfully, it returns a `Json` wrapper containing an `Option` of `Brewer::Model`.
-/// - `Err((StatusCode, String))` - If there is an error, it returns a tuple containing a `StatusCode` and a `String` with the error message.
+/// - `Ok(Json<Brewer::Model>)` - If the entity is found successfully, it returns a `Json` wrapper containing the `Brewer::Model`.
+/// - `Err(ApiError)` - If there is an error, it returns an `ApiError` that is rendered as a JSON error response.
 ///
 /// # Errors
 ///
 /// This function will return an error in the following cases:
-/// - If there is an issue with the database connection or query execution, it will return an internal server error represented by a tuple containing an appropriate `StatusCode` and an error message.
+/// - If no brewer has the given ID, it will return `ApiError::NotFound`, rendered as a 404 JSON error response.
+/// - If there is an issue with the database connection or query execution, it will return an `ApiError::Internal`, rendered as a 500 JSON error response.
+#[utoipa::path(
+    get,
+    path = "/api/v1/brewers/{id}",
+    tag = "brewers",
+    summary = "Get a brewer",
+    params(("id" = i32, Path, description = "Id of the brewer")),
+    responses(
+        (status = 200, description = "The brewer", body = Brewer::Model),
+        (status = 404, description = "No brewer with this id", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_brewer_id(
     State(conn): State<DatabaseConnection>,
     Path(id): Path<i32>,
-) -> Result<Json<Option<Brewer::Model>>, (StatusCode, String)> {
-    let brewer = Brewer::Entity::find_by_id(id)
-        .one(&conn)
-        .await
-        .map_err(internal_error)?;
+) -> Result<Json<Brewer::Model>, ApiError> {
+    let brewer = find_brewer(&conn, id).await?;
     Ok(Json(brewer))
 }
+
+/// Creates a new brewer. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `FormOrJson(form)` - The brewer to create, as a form or as JSON.
+///
+/// # Returns
+///
+/// * `Ok(Json(brewer))` - The newly created brewer.
+/// * `Err(ApiError)` - If the brewer could not be created.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/brewers",
+    tag = "brewers",
+    summary = "Create a brewer",
+    description = "Only available to admins. Only `type` is read from the body.",
+    request_body(content(
+        (Brewer::Model = "application/json"),
+        (Brewer::Model = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The created brewer", body = Brewer::Model),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn create_brewer(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    FormOrJson(form): FormOrJson<Brewer::Model>,
+) -> Result<Json<Brewer::Model>, ApiError> {
+    let brewer = Brewer::ActiveModel {
+        id: Default::default(),
+        r#type: ActiveValue::set(form.r#type),
+    };
+    let result = brewer.insert(&conn).await?;
+    Ok(Json(result))
+}
+
+/// Renames an existing brewer. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `Path(id)` - The ID of the brewer to rename.
+/// * `FormOrJson(form)` - The new brewer type, as a form or as JSON.
+///
+/// # Errors
+///
+/// * `ApiError::NotFound` if no brewer has the given ID.
+/// * `ApiError::Internal` if the database update fails.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/brewers/{id}",
+    tag = "brewers",
+    summary = "Update a brewer",
+    description = "Only available to admins. Only `type` is read from the body.",
+    params(("id" = i32, Path, description = "Id of the brewer")),
+    request_body(content(
+        (Brewer::Model = "application/json"),
+        (Brewer::Model = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated brewer", body = Brewer::Model),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "No brewer with this id", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn update_brewer(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    Path(id): Path<i32>,
+    FormOrJson(form): FormOrJson<Brewer::Model>,
+) -> Result<Json<Brewer::Model>, ApiError> {
+    let mut brewer: Brewer::ActiveModel = find_brewer(&conn, id).await?.into();
+    brewer.r#type = ActiveValue::set(form.r#type);
+    let result = brewer.update(&conn).await?;
+    Ok(Json(result))
+}
+
+/// Deletes a brewer. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `Path(id)` - The ID of the brewer to delete.
+///
+/// # Errors
+///
+/// * `ApiError::NotFound` if no brewer has the given ID.
+/// * `ApiError::Conflict` if any recipe still uses the brewer.
+/// * `ApiError::Internal` if the database delete fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/brewers/{id}",
+    tag = "brewers",
+    summary = "Delete a brewer",
+    description = "Only available to admins.",
+    params(("id" = i32, Path, description = "Id of the brewer")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "No brewer with this id", body = ErrorBody),
+        (status = 409, description = "Recipes still use the brewer", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn delete_brewer(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let brewer = find_brewer(&conn, id).await?;
+    let in_use = Recipe::Entity::find()
+        .filter(Recipe::Column::BrewerId.eq(brewer.id))
+        .count(&conn)
+        .await?;
+    if in_use > 0 {
+        return Err(ApiError::Conflict(format!(
+            "brewer is used by {in_use} recipe(s)"
+        )));
+    }
+    brewer.delete(&conn).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_brewer(conn: &DatabaseConnection, id: i32) -> Result<Brewer::Model, ApiError> {
+    Brewer::Entity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("brewer not found".to_string()))
+}
@@ -0,0 +1,377 @@
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
+use url::Url;
+
+/// File read when `CONFIG_FILE` is not set. It is optional.
+pub const DEFAULT_CONFIG_FILE: &str = "bloom.toml";
+
+/// Server settings.
+///
+/// Settings are resolved in this order, later sources winning:
+///
+/// 1. the defaults listed on each field,
+/// 2. the TOML file named by `CONFIG_FILE`, or `bloom.toml` if it exists,
+/// 3. environment variables, including any loaded from a `.env` file.
+///
+/// The TOML file uses the field names below as keys; the environment uses the
+/// same names upper-cased, e.g. `request_timeout_secs` / `REQUEST_TIMEOUT_SECS`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Database connection string, `postgres://...` or `sqlite://...`. Required.
+    pub database_url: String,
+    /// Address to bind to. Defaults to `127.0.0.1`.
+    pub host: String,
+    /// Port to listen on. Defaults to `3000`.
+    pub port: u16,
+    /// A `tracing` filter directive such as `info` or `bloom_api=debug,info`.
+    /// Defaults to `info`.
+    pub log_level: String,
+    /// Origins allowed to make cross-origin requests. `*` allows any origin,
+    /// which is the default. In the environment this is a comma separated list.
+    pub cors_origins: Vec<String>,
+    /// Seconds before a request is aborted with a 408. Defaults to `30`.
+    pub request_timeout_secs: u64,
+    /// Seconds to wait for in-flight requests after SIGTERM or Ctrl-C before
+    /// the server stops anyway. Defaults to `30`.
+    pub shutdown_timeout_secs: u64,
+    /// Largest number of pooled database connections. Defaults to `10`.
+    pub db_max_connections: u32,
+    /// Whether pending migrations are applied at start up. Defaults to `true`.
+    pub auto_migrate: bool,
+    /// Secret used to verify HS256 bearer tokens. Authenticated routes answer
+    /// with a 500 until it is set.
+    pub jwt_secret: Option<String>,
+    /// Whether write and search routes are rate limited. Defaults to `true`.
+    pub rate_limit_enabled: bool,
+    /// Requests per minute each client may make to each write route. Defaults to `30`.
+    pub rate_limit_writes_per_minute: u32,
+    /// Requests per minute each client may make to each search route. Defaults to `60`.
+    pub rate_limit_searches_per_minute: u32,
+    /// Addresses of reverse proxies whose `X-Forwarded-For` header is trusted to
+    /// name the client. Empty by default, so the peer address is always used.
+    /// In the environment this is a comma separated list.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// The shape of the optional TOML file. Every key may be left out.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    database_url: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    log_level: Option<String>,
+    cors_origins: Option<Vec<String>>,
+    request_timeout_secs: Option<u64>,
+    shutdown_timeout_secs: Option<u64>,
+    db_max_connections: Option<u32>,
+    auto_migrate: Option<bool>,
+    jwt_secret: Option<String>,
+    rate_limit_enabled: Option<bool>,
+    rate_limit_writes_per_minute: Option<u32>,
+    rate_limit_searches_per_minute: Option<u32>,
+    trusted_proxies: Option<Vec<IpAddr>>,
+}
+
+/// Why the configuration could not be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file exists but could not be read.
+    Read(PathBuf, std::io::Error),
+    /// The config file is not valid TOML or has unknown keys.
+    Parse(PathBuf, toml::de::Error),
+    /// A setting is missing or has an unusable value.
+    Invalid { key: &'static str, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "cannot read {}: {err}", path.display()),
+            ConfigError::Parse(path, err) => write!(f, "cannot parse {}: {err}", path.display()),
+            ConfigError::Invalid { key, message } => write!(f, "invalid `{key}`: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            database_url: String::new(),
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            log_level: "info".to_string(),
+            cors_origins: vec!["*".to_string()],
+            request_timeout_secs: 30,
+            shutdown_timeout_secs: 30,
+            db_max_connections: 10,
+            auto_migrate: true,
+            jwt_secret: None,
+            rate_limit_enabled: true,
+            rate_limit_writes_per_minute: 30,
+            rate_limit_searches_per_minute: 60,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `.env`, the optional TOML file and the environment, then validates the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConfigError` if the TOML file cannot be read or parsed, an
+    /// environment variable cannot be parsed, or [`Config::validate`] fails.
+    pub fn load() -> Result<Config, ConfigError> {
+        // A missing .env file is fine; real environment variables still apply.
+        dotenvy::dotenv().ok();
+
+        let mut config = Config::default();
+        match env::var("CONFIG_FILE") {
+            Ok(path) => config.merge_file(read_file(Path::new(&path))?),
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                config.merge_file(read_file(Path::new(DEFAULT_CONFIG_FILE))?)
+            }
+            Err(_) => {}
+        }
+        config.merge_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that every setting is usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::Invalid` naming the first bad setting.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.database_url.is_empty() {
+            return Err(invalid("database_url", "must be set"));
+        }
+        if self.host.is_empty() {
+            return Err(invalid("host", "must not be empty"));
+        }
+        if let Err(err) = EnvFilter::try_new(&self.log_level) {
+            return Err(invalid("log_level", err.to_string()));
+        }
+        if self.cors_origins.is_empty() {
+            return Err(invalid(
+                "cors_origins",
+                "must list at least one origin or `*`",
+            ));
+        }
+        for origin in &self.cors_origins {
+            if origin != "*" && !is_origin(origin) {
+                return Err(invalid(
+                    "cors_origins",
+                    format!("`{origin}` is not an origin like https://example.com"),
+                ));
+            }
+        }
+        if self.request_timeout_secs == 0 {
+            return Err(invalid("request_timeout_secs", "must be greater than 0"));
+        }
+        if self.db_max_connections == 0 {
+            return Err(invalid("db_max_connections", "must be greater than 0"));
+        }
+        if self.rate_limit_writes_per_minute == 0 {
+            return Err(invalid(
+                "rate_limit_writes_per_minute",
+                "must be greater than 0; set rate_limit_enabled = false to disable rate limiting",
+            ));
+        }
+        if self.rate_limit_searches_per_minute == 0 {
+            return Err(invalid(
+                "rate_limit_searches_per_minute",
+                "must be greater than 0; set rate_limit_enabled = false to disable rate limiting",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The `host:port` pair to bind to.
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// The request timeout as a `Duration`.
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    /// How long to drain in-flight requests on shutdown, as a `Duration`.
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    /// True if any origin may make cross-origin requests.
+    pub fn allows_any_origin(&self) -> bool {
+        self.cors_origins.iter().any(|origin| origin == "*")
+    }
+
+    fn merge_file(&mut self, file: FileConfig) {
+        let FileConfig {
+            database_url,
+            host,
+            port,
+            log_level,
+            cors_origins,
+            request_timeout_secs,
+            shutdown_timeout_secs,
+            db_max_connections,
+            auto_migrate,
+            jwt_secret,
+            rate_limit_enabled,
+            rate_limit_writes_per_minute,
+            rate_limit_searches_per_minute,
+            trusted_proxies,
+        } = file;
+        set(&mut self.database_url, database_url);
+        set(&mut self.host, host);
+        set(&mut self.port, port);
+        set(&mut self.log_level, log_level);
+        set(&mut self.cors_origins, cors_origins);
+        set(&mut self.request_timeout_secs, request_timeout_secs);
+        set(&mut self.shutdown_timeout_secs, shutdown_timeout_secs);
+        set(&mut self.db_max_connections, db_max_connections);
+        set(&mut self.auto_migrate, auto_migrate);
+        if jwt_secret.is_some() {
+            self.jwt_secret = jwt_secret;
+        }
+        set(&mut self.rate_limit_enabled, rate_limit_enabled);
+        set(
+            &mut self.rate_limit_writes_per_minute,
+            rate_limit_writes_per_minute,
+        );
+        set(
+            &mut self.rate_limit_searches_per_minute,
+            rate_limit_searches_per_minute,
+        );
+        set(&mut self.trusted_proxies, trusted_proxies);
+    }
+
+    fn merge_env(&mut self) -> Result<(), ConfigError> {
+        set(&mut self.database_url, env_var("DATABASE_URL"));
+        set(&mut self.host, env_var("HOST"));
+        if let Some(secret) = env_var("JWT_SECRET") {
+            self.jwt_secret = Some(secret);
+        }
+        set(&mut self.port, parse_env("PORT", "port")?);
+        set(&mut self.log_level, env_var("LOG_LEVEL"));
+        set(
+            &mut self.cors_origins,
+            env_var("CORS_ORIGINS").map(|origins| split_list(&origins)),
+        );
+        set(
+            &mut self.request_timeout_secs,
+            parse_env("REQUEST_TIMEOUT_SECS", "request_timeout_secs")?,
+        );
+        set(
+            &mut self.shutdown_timeout_secs,
+            parse_env("SHUTDOWN_TIMEOUT_SECS", "shutdown_timeout_secs")?,
+        );
+        set(
+            &mut self.db_max_connections,
+            parse_env("DB_MAX_CONNECTIONS", "db_max_connections")?,
+        );
+        set(
+            &mut self.auto_migrate,
+            parse_env("AUTO_MIGRATE", "auto_migrate")?,
+        );
+        set(
+            &mut self.rate_limit_enabled,
+            parse_env("RATE_LIMIT_ENABLED", "rate_limit_enabled")?,
+        );
+        set(
+            &mut self.rate_limit_writes_per_minute,
+            parse_env(
+                "RATE_LIMIT_WRITES_PER_MINUTE",
+                "rate_limit_writes_per_minute",
+            )?,
+        );
+        set(
+            &mut self.rate_limit_searches_per_minute,
+            parse_env(
+                "RATE_LIMIT_SEARCHES_PER_MINUTE",
+                "rate_limit_searches_per_minute",
+            )?,
+        );
+        if let Some(proxies) = env_var("TRUSTED_PROXIES") {
+            self.trusted_proxies = split_list(&proxies)
+                .iter()
+                .map(|proxy| {
+                    proxy.parse().map_err(|err| {
+                        invalid("trusted_proxies", format!("TRUSTED_PROXIES={proxy}: {err}"))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(())
+    }
+}
+
+fn read_file(path: &Path) -> Result<FileConfig, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|err| ConfigError::Read(path.into(), err))?;
+    toml::from_str(&text).map_err(|err| ConfigError::Parse(path.into(), err))
+}
+
+fn set<T>(field: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *field = value;
+    }
+}
+
+/// Reads an environment variable, treating an empty value as unset.
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn parse_env<T>(name: &str, key: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    env_var(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|err| invalid(key, format!("{name}={value}: {err}")))
+        })
+        .transpose()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Returns true for a bare `scheme://host[:port]` origin.
+fn is_origin(origin: &str) -> bool {
+    Url::parse(origin).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https")
+            && url.host_str().is_some()
+            && url.path() == "/"
+            && !origin.ends_with('/')
+            && url.query().is_none()
+    })
+}
+
+fn invalid(key: &'static str, message: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        key,
+        message: message.into(),
+    }
+}
@@ -0,0 +1,219 @@
+use axum::{
+    extract::rejection::{FormRejection, JsonRejection, PathRejection, QueryRejection},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::{DbErr, SqlErr};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Error returned by every handler.
+///
+/// Each variant maps to an HTTP status and a stable, machine readable `code`.
+/// The response body is always JSON:
+///
+/// ```json
+/// { "code": "not_found", "message": "recipe not found" }
+/// ```
+///
+/// Internal errors only ever report a generic message to the client; the
+/// underlying cause is written to the log.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 400: the request could not be understood.
+    BadRequest(String),
+    /// 401: no valid credentials were supplied.
+    Unauthorized(String),
+    /// 403: the caller is authenticated but not allowed to do this.
+    Forbidden(String),
+    /// 404: the requested resource does not exist.
+    NotFound(String),
+    /// 408: the request took too long to process.
+    Timeout,
+    /// 409: the request conflicts with existing data.
+    Conflict(String),
+    /// 409: the xBloom recipe was already shared as the recipe with `slug`. The
+    /// response carries the slug and a `Location` header pointing at it.
+    DuplicateRecipe { slug: String },
+    /// 422: the request was well formed but its content is invalid.
+    Unprocessable(String),
+    /// 422: one or more fields failed validation.
+    Invalid(Vec<FieldError>),
+    /// 429: the client used up its rate limit budget. The response carries a
+    /// `Retry-After` header with the number of seconds to wait.
+    TooManyRequests { retry_after: u64 },
+    /// 500: something went wrong on our side. The message is only logged.
+    Internal(String),
+}
+
+/// JSON body sent for every [`ApiError`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    /// Per-field problems, only present for validation errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// Slug of the recipe a duplicate submission matched, only present for
+    /// `duplicate_recipe` errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub existing_slug: Option<String>,
+}
+
+/// A validation problem with a single request field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ApiError {
+    /// The HTTP status sent for this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Timeout => StatusCode::REQUEST_TIMEOUT,
+            ApiError::Conflict(_) | ApiError::DuplicateRecipe { .. } => StatusCode::CONFLICT,
+            ApiError::Unprocessable(_) | ApiError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// The stable error code sent to clients.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Timeout => "timeout",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::DuplicateRecipe { .. } => "duplicate_recipe",
+            ApiError::Unprocessable(_) => "unprocessable_entity",
+            ApiError::Invalid(_) => "validation_failed",
+            ApiError::TooManyRequests { .. } => "rate_limited",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// The message sent to clients.
+    fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::Conflict(msg)
+            | ApiError::Unprocessable(msg) => msg.clone(),
+            ApiError::DuplicateRecipe { slug } => {
+                format!("this xBloom recipe was already shared as {slug}")
+            }
+            ApiError::Invalid(_) => "the request has invalid fields".to_string(),
+            ApiError::Timeout => "request timed out".to_string(),
+            ApiError::TooManyRequests { retry_after } => {
+                format!("too many requests, retry in {retry_after}s")
+            }
+            ApiError::Internal(_) => "internal server error".to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let ApiError::Internal(detail) = &self {
+            tracing::error!("internal error: {detail}");
+        }
+        let status = self.status();
+        let code = self.code().to_string();
+        let message = self.message();
+        let retry_after = match self {
+            ApiError::TooManyRequests { retry_after } => Some(retry_after),
+            _ => None,
+        };
+        let (fields, existing_slug) = match self {
+            ApiError::Invalid(fields) => (fields, None),
+            ApiError::DuplicateRecipe { slug } => (Vec::new(), Some(slug)),
+            _ => (Vec::new(), None),
+        };
+        let location = existing_slug
+            .as_deref()
+            .and_then(|slug| HeaderValue::from_str(&format!("/api/v1/recipes/slug/{slug}")).ok());
+        let body = ErrorBody {
+            code,
+            message,
+            fields,
+            existing_slug,
+        };
+        let mut response = (status, Json(body)).into_response();
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        if let Some(location) = location {
+            response.headers_mut().insert(header::LOCATION, location);
+        }
+        response
+    }
+}
+
+/// Database errors become a 409 for unique violations, a 422 for foreign key
+/// violations and a 500 for anything else.
+impl From<DbErr> for ApiError {
+    fn from(err: DbErr) -> Self {
+        match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => {
+                ApiError::Conflict("a record with the same unique value already exists".to_string())
+            }
+            Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+                ApiError::Unprocessable("a referenced record does not exist".to_string())
+            }
+            _ => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+/// Body rejections keep axum's explanation; bodies that parse but do not match
+/// the expected shape are a 422, everything else is a 400.
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        body_rejection(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<FormRejection> for ApiError {
+    fn from(rejection: FormRejection) -> Self {
+        body_rejection(rejection.status(), rejection.body_text())
+    }
+}
+
+/// Path and query rejections are a 400, except for a route that is missing the
+/// parameters its handler expects, which is our bug.
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        if rejection.status().is_server_error() {
+            ApiError::Internal(rejection.body_text())
+        } else {
+            ApiError::BadRequest(rejection.body_text())
+        }
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+fn body_rejection(status: StatusCode, message: String) -> ApiError {
+    if status == StatusCode::UNPROCESSABLE_ENTITY {
+        ApiError::Unprocessable(message)
+    } else {
+        ApiError::BadRequest(message)
+    }
+}
@@ -0,0 +1,63 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Request},
+    http::header::CONTENT_TYPE,
+    Form, Json,
+};
+use serde::de::DeserializeOwned;
+
+use super::error::ApiError;
+
+/// Request body extractor that accepts either JSON or a url-encoded form.
+///
+/// Requests sent with an `application/json` (or `+json`) content type are parsed as
+/// JSON and everything else is parsed as a form, so existing form based clients keep
+/// working while scripts can post JSON directly.
+#[derive(Clone, Debug)]
+pub struct FormOrJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for FormOrJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|mime| {
+                let mime = mime.trim();
+                mime == "application/json" || mime.ends_with("+json")
+            })
+            .unwrap_or(false);
+
+        if is_json {
+            let Json(value) = Json::<T>::from_request(req, state).await?;
+            Ok(FormOrJson(value))
+        } else {
+            let Form(value) = Form::<T>::from_request(req, state).await?;
+            Ok(FormOrJson(value))
+        }
+    }
+}
+
+/// Path parameter extractor that reports malformed parameters as an [`ApiError`].
+///
+/// Works like [`axum::extract::Path`], but a parameter that does not parse, e.g.
+/// `/recipes/abc` for an `i32` id, is answered with the usual JSON error body
+/// instead of axum's plain text.
+#[derive(Clone, Copy, Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct Path<T>(pub T);
+
+/// Query string extractor that reports malformed parameters as an [`ApiError`].
+///
+/// Works like [`axum::extract::Query`], see [`Path`].
+#[derive(Clone, Copy, Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct Query<T>(pub T);
@@ -0,0 +1,108 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Version of the running build, taken from the crate version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Body returned by [`healthz`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Health {
+    pub status: String,
+    pub version: String,
+}
+
+/// Body returned by [`readyz`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Readiness {
+    /// `ready` or `unavailable`.
+    pub status: String,
+    pub version: String,
+    /// `ok` or `unreachable`.
+    pub database: String,
+    /// Names of migrations that have not been applied yet.
+    pub pending_migrations: Vec<String>,
+}
+
+/// Liveness probe.
+///
+/// Answers as long as the process is serving requests. The database is
+/// deliberately not checked so an outage does not get healthy instances
+/// restarted; use [`readyz`] to take an instance out of rotation instead.
+///
+/// # Returns
+///
+/// * `Json<Health>` - Always `StatusCode::OK` with `status: "ok"` and the build version.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    summary = "Liveness probe",
+    responses(
+        (status = 200, description = "The process is serving requests", body = Health),
+    )
+)]
+pub async fn healthz() -> Json<Health> {
+    Json(Health {
+        status: "ok".to_string(),
+        version: VERSION.to_string(),
+    })
+}
+
+/// Readiness probe.
+///
+/// Pings the database and lists migrations that still have to be applied.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+///
+/// # Returns
+///
+/// * `StatusCode::OK` with `status: "ready"` if the database answers and every
+///   migration has been applied.
+/// * `StatusCode::SERVICE_UNAVAILABLE` with `status: "unavailable"` otherwise. The
+///   underlying database error is logged, not returned.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    summary = "Readiness probe",
+    responses(
+        (status = 200, description = "The database answers and is fully migrated", body = Readiness),
+        (status = 503, description = "The database is unreachable or has pending migrations", body = Readiness),
+    )
+)]
+pub async fn readyz(State(conn): State<DatabaseConnection>) -> (StatusCode, Json<Readiness>) {
+    let (database, pending_migrations) = match check_database(&conn).await {
+        Ok(pending) => ("ok", pending),
+        Err(err) => {
+            tracing::warn!("readiness check failed: {err}");
+            ("unreachable", Vec::new())
+        }
+    };
+    let ready = database == "ok" && pending_migrations.is_empty();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = Readiness {
+        status: if ready { "ready" } else { "unavailable" }.to_string(),
+        version: VERSION.to_string(),
+        database: database.to_string(),
+        pending_migrations,
+    };
+    (status, Json(body))
+}
+
+async fn check_database(conn: &DatabaseConnection) -> Result<Vec<String>, sea_orm::DbErr> {
+    conn.ping().await?;
+    let pending = Migrator::get_pending_migrations(conn).await?;
+    Ok(pending
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect())
+}
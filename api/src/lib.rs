@@ -1,25 +1,21 @@
-use axum::http::StatusCode;
+#[cfg(not(any(feature = "postgres", feature = "sqlite")))]
+compile_error!("enable at least one database backend: the `postgres` or `sqlite` feature");
 
+pub mod auth;
 mod brewers;
+pub mod config;
+pub mod error;
+mod extract;
+pub mod health;
+pub mod metrics;
+pub mod openapi;
+pub mod pagination;
+pub mod rate_limit;
+mod recipe_detail;
 mod recipes;
 mod roasts;
 pub mod server;
+mod tags;
 mod users;
-
-/// Converts an internal error into a tuple containing an HTTP status code and an error message.
-///
-/// # Arguments
-///
-/// * `err` - The error that occurred. It must implement the `std::error::Error` trait.
-///
-/// # Returns
-///
-/// A tuple containing:
-/// * `StatusCode::INTERNAL_SERVER_ERROR` - Represents the 500 Internal Server Error HTTP status code.
-/// * `String` - The string representation of the error.
-pub fn internal_error<E>(err: E) -> (StatusCode, String)
-where
-    E: std::error::Error,
-{
-    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
-}
+mod validation;
+mod xbloom;
@@ -0,0 +1,157 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Requests served, labelled by `method`, `route` and `status`.
+pub const HTTP_REQUESTS: &str = "http_requests_total";
+/// Request latency in seconds, labelled like [`HTTP_REQUESTS`].
+pub const HTTP_REQUEST_DURATION: &str = "http_request_duration_seconds";
+/// Database statement latency in seconds, labelled by `outcome`.
+pub const DB_QUERY_DURATION: &str = "db_query_duration_seconds";
+/// Pooled database connections, labelled by `state` (`idle` or `in_use`).
+pub const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+/// Largest number of connections the pool may open.
+pub const DB_POOL_MAX_CONNECTIONS: &str = "db_pool_max_connections";
+/// Recipes created through the API.
+pub const RECIPES_CREATED: &str = "recipes_created_total";
+/// Recipe searches run, labelled by `mode` (`full_text`, `substring` or `filter`).
+pub const RECIPE_SEARCHES: &str = "recipe_searches_total";
+
+/// Histogram buckets, in seconds, for request and statement latencies.
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Returns a [`PrometheusBuilder`] with the latency buckets bloomdb reports with.
+///
+/// Applications embedding the API that install their own recorder can start from
+/// this builder and pass the resulting handle to [`AppState::with_metrics`].
+///
+/// [`AppState::with_metrics`]: crate::server::AppState::with_metrics
+pub fn builder() -> PrometheusBuilder {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )
+        .expect("latency buckets are not empty")
+}
+
+/// Returns the handle to the process wide Prometheus recorder, installing the
+/// recorder on first use.
+///
+/// If another `metrics` recorder was installed first, e.g. by an application
+/// embedding the API, the returned handle belongs to a recorder that is not
+/// installed: every metric goes to the recorder that was already there and
+/// `/metrics` serves an empty page.
+pub fn handle() -> PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+    HANDLE
+        .get_or_init(|| {
+            let recorder = builder().build_recorder();
+            let handle = recorder.handle();
+            if metrics::set_global_recorder(recorder).is_err() {
+                tracing::warn!("another metrics recorder is installed, /metrics will be empty");
+            }
+            handle
+        })
+        .clone()
+}
+
+/// Middleware recording [`HTTP_REQUESTS`] and [`HTTP_REQUEST_DURATION`] for every request.
+///
+/// The `route` label is the matched route pattern, e.g. `/api/v1/recipes/:id`, so
+/// ids in paths do not create new series. Requests that match no route are
+/// counted under `unmatched`.
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!(HTTP_REQUESTS, &labels).increment(1);
+    histogram!(HTTP_REQUEST_DURATION, &labels).record(start.elapsed().as_secs_f64());
+    response
+}
+
+/// Makes `conn` record [`DB_QUERY_DURATION`] for every statement it runs.
+pub fn instrument_connection(conn: &mut DatabaseConnection) {
+    conn.set_metric_callback(|info| {
+        let outcome = if info.failed { "error" } else { "ok" };
+        histogram!(DB_QUERY_DURATION, "outcome" => outcome).record(info.elapsed.as_secs_f64());
+    });
+}
+
+/// Serves every recorded metric in the Prometheus text format.
+///
+/// Pool gauges are refreshed on each scrape.
+///
+/// # Arguments
+///
+/// * `State(handle)` - The Prometheus recorder handle.
+/// * `State(conn)` - The database connection whose pool is reported.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    summary = "Prometheus metrics",
+    responses(
+        (status = 200, description = "Every metric in the Prometheus text format", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn render(
+    State(handle): State<PrometheusHandle>,
+    State(conn): State<DatabaseConnection>,
+) -> impl IntoResponse {
+    record_pool_stats(&conn);
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        handle.render(),
+    )
+}
+
+fn record_pool_stats(conn: &DatabaseConnection) {
+    let (size, idle, max) = match conn.get_database_backend() {
+        #[cfg(feature = "postgres")]
+        DbBackend::Postgres => {
+            let pool = conn.get_postgres_connection_pool();
+            (
+                pool.size(),
+                pool.num_idle(),
+                pool.options().get_max_connections(),
+            )
+        }
+        #[cfg(feature = "sqlite")]
+        DbBackend::Sqlite => {
+            let pool = conn.get_sqlite_connection_pool();
+            (
+                pool.size(),
+                pool.num_idle(),
+                pool.options().get_max_connections(),
+            )
+        }
+        _ => return,
+    };
+    let idle = idle as f64;
+    gauge!(DB_POOL_CONNECTIONS, "state" => "idle").set(idle);
+    gauge!(DB_POOL_CONNECTIONS, "state" => "in_use").set((f64::from(size) - idle).max(0.0));
+    gauge!(DB_POOL_MAX_CONNECTIONS).set(f64::from(max));
+}
@@ -0,0 +1,113 @@
+use axum::response::{Html, Json};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::{brewers, health, metrics, recipes, roasts, server, tags, users};
+
+/// Name of the security scheme that authenticated operations refer to.
+pub const BEARER_AUTH: &str = "bearer";
+
+/// The OpenAPI 3 description of every bloomdb route.
+///
+/// Only the `/api/v1` paths are described; the deprecated unversioned aliases
+/// are left out.
+///
+/// Operations come from the `#[utoipa::path]` attribute on each handler and
+/// schemas from the `ToSchema` derives on the request and response types,
+/// including the `entity` models.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "bloomdb",
+        description = "Community recipe sharing for xBloom coffee machines.",
+        license(name = "MIT")
+    ),
+    paths(
+        server::handler,
+        health::healthz,
+        health::readyz,
+        metrics::render,
+        roasts::get_roasts,
+        roasts::get_roast_id,
+        roasts::create_roast,
+        roasts::update_roast,
+        roasts::delete_roast,
+        brewers::get_brewers,
+        brewers::get_brewer_id,
+        brewers::create_brewer,
+        brewers::update_brewer,
+        brewers::delete_brewer,
+        users::get_users,
+        users::get_user_id,
+        recipes::get_recipes,
+        recipes::get_recipe_id,
+        recipes::get_recipe_slug,
+        recipes::get_recipe_title,
+        recipes::get_recipes_roaster,
+        recipes::get_recipes_machine,
+        recipes::search_recipes,
+        recipes::get_recipes_tag,
+        recipes::create_recipe,
+        recipes::update_recipe,
+        recipes::patch_recipe,
+        recipes::delete_recipe,
+        tags::get_tags,
+        tags::get_recipe_tags,
+        tags::add_recipe_tags,
+        tags::remove_recipe_tag,
+        tags::create_tag,
+        tags::update_tag,
+        tags::delete_tag,
+    ),
+    // Query parameter enums are not collected from the handlers automatically.
+    components(schemas(recipes::RecipeSort)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "recipes", description = "Recipes and recipe search"),
+        (name = "tags", description = "Tags and tagging recipes"),
+        (name = "brewers", description = "The brewer lookup table"),
+        (name = "roasts", description = "The roast level lookup table"),
+        (name = "users", description = "Public user profiles"),
+        (name = "health", description = "Probes and metrics for operators"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the HS256 bearer token scheme used by [`crate::auth::AuthUser`].
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let mut scheme = Http::new(HttpAuthScheme::Bearer);
+        scheme.bearer_format = Some("JWT".to_string());
+        components.add_security_scheme(BEARER_AUTH, SecurityScheme::Http(scheme));
+    }
+}
+
+/// Serves the OpenAPI document as JSON.
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Serves an interactive API reference for [`openapi_json`].
+///
+/// The page loads the Scalar API reference viewer from its CDN, so the browser
+/// needs internet access; the spec itself is fetched from this server.
+pub async fn docs() -> Html<&'static str> {
+    Html(DOCS_PAGE)
+}
+
+const DOCS_PAGE: &str = r#"<!doctype html>
+<html>
+  <head>
+    <title>bloomdb API</title>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+  </head>
+  <body>
+    <script id="api-reference" data-url="/api/v1/openapi.json"></script>
+    <script src="https://cdn.jsdelivr.net/npm/@scalar/api-reference"></script>
+  </body>
+</html>
+"#;
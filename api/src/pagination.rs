@@ -0,0 +1,195 @@
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, FromQueryResult, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::ApiError;
+
+/// Number of items returned when the request does not ask for a page size.
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+/// Largest page size a client may request.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// Query parameters accepted by every list endpoint.
+///
+/// Two styles are supported:
+/// - cursor based: `?limit=20&cursor=42` returns the rows after id `42`,
+/// - offset based: `?page=3&per_page=20` returns the third page.
+///
+/// `limit` and `per_page` are interchangeable. When both `cursor` and `page` are
+/// given the cursor wins.
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Pagination {
+    /// Page size, at most 100. Defaults to 20.
+    pub limit: Option<u64>,
+    /// Return the rows after this id, taken from `next_cursor`.
+    pub cursor: Option<i32>,
+    /// 1-based page number, used when no `cursor` is given.
+    pub page: Option<u64>,
+    /// Same as `limit`.
+    pub per_page: Option<u64>,
+}
+
+impl Pagination {
+    /// The page size to use, clamped to `1..=MAX_PAGE_SIZE`.
+    pub fn page_size(&self) -> u64 {
+        self.limit
+            .or(self.per_page)
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// The number of rows to skip for `?page=`, or `0` without a page.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ApiError::BadRequest` if the offset does not fit the signed 64-bit
+    /// `OFFSET` the databases accept.
+    pub fn offset(&self) -> Result<u64, ApiError> {
+        let page = self.page.unwrap_or(1);
+        page.saturating_sub(1)
+            .checked_mul(self.page_size())
+            .filter(|offset| i64::try_from(*offset).is_ok())
+            .ok_or_else(|| ApiError::BadRequest(format!("page {page} is out of range")))
+    }
+}
+
+/// Response envelope returned by list endpoints.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Pass this back as `?cursor=` to fetch the next page. `None` on the last page.
+    pub next_cursor: Option<i32>,
+    /// Number of rows matching the query across all pages.
+    pub total: u64,
+}
+
+/// Runs `query` one page at a time, ordered by the `id` column.
+///
+/// This is [`paginate_ordered`] with ascending order.
+pub async fn paginate<E, M, C>(
+    conn: &C,
+    query: Select<E>,
+    id: E::Column,
+    params: &Pagination,
+    id_of: impl Fn(&M) -> i32,
+) -> Result<Paginated<M>, ApiError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    M: FromQueryResult + Send + Sync,
+    C: ConnectionTrait,
+{
+    paginate_ordered(conn, query, id, Order::Asc, params, id_of).await
+}
+
+/// Runs `query` one page at a time, ordered by the `id` column in `order`.
+///
+/// # Arguments
+///
+/// * `conn` - The connection to run the query on.
+/// * `query` - The query to paginate, with any filters already applied.
+/// * `id` - The primary key column used for ordering and as the cursor.
+/// * `order` - Whether to walk the ids upwards or downwards.
+/// * `params` - The pagination parameters from the request.
+/// * `id_of` - Reads the primary key back out of a returned row.
+///
+/// # Errors
+///
+/// Returns `ApiError::BadRequest` if `params.page` is out of range, and the
+/// `DbErr` from either the count or the page query as `ApiError::Internal`.
+pub async fn paginate_ordered<E, M, C>(
+    conn: &C,
+    query: Select<E>,
+    id: E::Column,
+    order: Order,
+    params: &Pagination,
+    id_of: impl Fn(&M) -> i32,
+) -> Result<Paginated<M>, ApiError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    M: FromQueryResult + Send + Sync,
+    C: ConnectionTrait,
+{
+    let total = query.clone().count(conn).await?;
+    let size = params.page_size();
+
+    let mut query = query.order_by(id, order.clone());
+    if let Some(cursor) = params.cursor {
+        query = match order {
+            Order::Desc => query.filter(id.lt(cursor)),
+            _ => query.filter(id.gt(cursor)),
+        };
+    } else if params.page.is_some() {
+        query = query.offset(params.offset()?);
+    }
+
+    // Fetch one extra row to find out whether there is a next page.
+    let mut items = query.limit(size + 1).into_model::<M>().all(conn).await?;
+    let next_cursor = if items.len() as u64 > size {
+        items.truncate(size as usize);
+        items.last().map(&id_of)
+    } else {
+        None
+    };
+
+    Ok(Paginated {
+        items,
+        next_cursor,
+        total,
+    })
+}
+
+/// Runs `query` one page at a time, keeping the ordering already set on `query`.
+///
+/// Used for result sets ordered by something other than the id, e.g. a
+/// timestamp with the id as tie-breaker. The cursor is still the id of the last
+/// row returned; the caller turns it into `after`, the condition matching the
+/// rows that sort after that row. Without a cursor `?page=` is used.
+///
+/// # Errors
+///
+/// Returns `ApiError::BadRequest` if `params.page` is out of range, and the
+/// `DbErr` from either the count or the page query as `ApiError::Internal`.
+pub async fn paginate_keyset<E, M, C>(
+    conn: &C,
+    query: Select<E>,
+    after: Option<Condition>,
+    params: &Pagination,
+    id_of: impl Fn(&M) -> i32,
+) -> Result<Paginated<M>, ApiError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    M: FromQueryResult + Send + Sync,
+    C: ConnectionTrait,
+{
+    let total = query.clone().count(conn).await?;
+    let size = params.page_size();
+
+    let mut query = query;
+    if let Some(after) = after {
+        query = query.filter(after);
+    } else if params.page.is_some() {
+        query = query.offset(params.offset()?);
+    }
+
+    // Fetch one extra row to find out whether there is a next page.
+    let mut items = query.limit(size + 1).into_model::<M>().all(conn).await?;
+    let next_cursor = if items.len() as u64 > size {
+        items.truncate(size as usize);
+        items.last().map(&id_of)
+    } else {
+        None
+    };
+
+    Ok(Paginated {
+        items,
+        next_cursor,
+        total,
+    })
+}
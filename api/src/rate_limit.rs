@@ -0,0 +1,305 @@
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::auth::{bearer_token, JwtKey};
+use crate::config::Config;
+use crate::error::ApiError;
+
+/// Routes that run a search and get the search budget. Every other route is
+/// only limited for unsafe methods, which get the write budget.
+const SEARCH_ROUTES: &[&str] = &["/api/v1/recipes/search", "/api/v1/recipes/title/:title"];
+
+/// Deprecated unversioned route prefixes and the `/api/v1` prefixes they alias,
+/// most specific first. Any other `/api/...` route is the same path under `/api/v1`.
+const LEGACY_PREFIXES: &[(&str, &str)] = &[
+    ("/api/recipe/create", "/api/v1/recipes"),
+    ("/api/recipe/id/", "/api/v1/recipes/"),
+    ("/api/recipe/title/", "/api/v1/recipes/title/"),
+    ("/api/recipe/", "/api/v1/recipes/slug/"),
+    ("/api/roast/", "/api/v1/roasts/"),
+    ("/api/brewer/", "/api/v1/brewers/"),
+    ("/api/user/", "/api/v1/users/"),
+    ("/api/admin/roast/", "/api/v1/admin/roasts/"),
+    ("/api/admin/brewer/", "/api/v1/admin/brewers/"),
+    ("/api/admin/tag/", "/api/v1/admin/tags/"),
+];
+
+/// Set once the missing peer address warning has been logged.
+static WARNED_NO_PEER: AtomicBool = AtomicBool::new(false);
+
+/// Number of buckets kept before idle ones are dropped.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+const RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// How many requests a client may make to a route within a window.
+///
+/// Budgets are enforced as token buckets: a client starts with `capacity`
+/// tokens, each request spends one, and tokens come back at an even rate so the
+/// bucket is full again one `window` after it was emptied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub capacity: u32,
+    pub window: Duration,
+}
+
+impl Budget {
+    /// A budget of `capacity` requests per minute.
+    pub fn per_minute(capacity: u32) -> Self {
+        Budget {
+            capacity,
+            window: Duration::from_secs(60),
+        }
+    }
+
+    /// Tokens returned to a bucket per second.
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.capacity) / self.window.as_secs_f64()
+    }
+}
+
+/// Per-client token buckets for the write and search routes.
+///
+/// Clients are identified by the user id in a valid bearer token, falling back
+/// to their IP address. Anonymous requests are only limited when the router is
+/// served with `into_make_service_with_connect_info::<SocketAddr>()`, which
+/// provides that address. The limiter is cheap to clone; clones share buckets.
+#[derive(Clone, Default)]
+pub struct RateLimiter(Option<Arc<Limits>>);
+
+struct Limits {
+    writes: Budget,
+    searches: Budget,
+    trusted_proxies: Vec<IpAddr>,
+    buckets: Mutex<HashMap<(String, String), Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The state of a client's bucket after a request, sent as `RateLimit-*` headers.
+struct Quota {
+    budget: Budget,
+    remaining: u32,
+    /// Seconds until the bucket is full again.
+    reset: u64,
+}
+
+impl RateLimiter {
+    /// Builds the limiter from the `rate_limit_*` and `trusted_proxies` settings.
+    ///
+    /// A disabled limiter lets every request through without headers.
+    pub fn new(config: &Config) -> Self {
+        if !config.rate_limit_enabled {
+            return RateLimiter(None);
+        }
+        RateLimiter(Some(Arc::new(Limits {
+            writes: Budget::per_minute(config.rate_limit_writes_per_minute),
+            searches: Budget::per_minute(config.rate_limit_searches_per_minute),
+            trusted_proxies: config.trusted_proxies.clone(),
+            buckets: Mutex::new(HashMap::new()),
+        })))
+    }
+}
+
+/// Maps a deprecated route pattern to the `/api/v1` pattern it aliases, so both
+/// spellings of a route share one bucket. Other patterns are returned unchanged.
+fn canonical_route(route: &str) -> String {
+    if route.starts_with("/api/v1/") {
+        return route.to_string();
+    }
+    for (legacy, v1) in LEGACY_PREFIXES {
+        if let Some(rest) = route.strip_prefix(legacy) {
+            return format!("{v1}{rest}");
+        }
+    }
+    match route.strip_prefix("/api/") {
+        Some(rest) => format!("/api/v1/{rest}"),
+        None => route.to_string(),
+    }
+}
+
+impl Limits {
+    /// The budget for `method` on the route pattern `route`, or `None` if the
+    /// route is not limited.
+    fn budget_for(&self, method: &Method, route: &str) -> Option<Budget> {
+        if SEARCH_ROUTES.contains(&route) {
+            Some(self.searches)
+        } else if !method.is_safe() {
+            Some(self.writes)
+        } else {
+            None
+        }
+    }
+
+    /// The bucket key for the client that sent `request`.
+    ///
+    /// Returns `None` for an anonymous request without a peer address, which is
+    /// what every request looks like when the router is served without
+    /// `into_make_service_with_connect_info`. Such requests are not limited:
+    /// putting them all in one bucket would let one client use up everyone's
+    /// budget.
+    fn client_key(&self, request: &Request, jwt: &JwtKey) -> Option<String> {
+        if let Some(claims) = bearer_token(request.headers()).and_then(|token| jwt.verify(token)) {
+            return Some(format!("user:{}", claims.sub));
+        }
+        let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+            if !WARNED_NO_PEER.swap(true, Ordering::Relaxed) {
+                tracing::warn!(
+                    "no client address on the request, anonymous requests are not rate limited; \
+                     serve the router with into_make_service_with_connect_info::<SocketAddr>()"
+                );
+            }
+            return None;
+        };
+        Some(format!(
+            "ip:{}",
+            self.client_ip(peer.ip(), request.headers())
+        ))
+    }
+
+    /// Resolves the client address behind any trusted proxies.
+    ///
+    /// `X-Forwarded-For` is read right to left, one hop at a time, for as long
+    /// as the hop that added the entry is a trusted proxy. Entries a client
+    /// wrote itself are therefore never believed.
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let forwarded: Vec<&str> = headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+
+        let mut client = peer;
+        for entry in forwarded.iter().rev() {
+            if !self.trusted_proxies.contains(&client) {
+                break;
+            }
+            match entry.trim().parse() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client
+    }
+
+    /// Spends a token from the bucket for `key`.
+    ///
+    /// Returns the remaining quota, or the number of seconds until a token is
+    /// available again if the bucket is empty.
+    fn take(&self, key: (String, String), budget: Budget) -> Result<Quota, (Quota, u64)> {
+        let now = Instant::now();
+        let mut buckets = self
+            .buckets
+            .lock()
+            .expect("rate limit buckets are not poisoned");
+        if buckets.len() >= PRUNE_THRESHOLD {
+            // A bucket left alone for a whole window is full, the same as a new one.
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated) < budget.window);
+        }
+
+        let capacity = f64::from(budget.capacity);
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * budget.refill_rate()).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        let seconds_until = |tokens: f64| (tokens / budget.refill_rate()).ceil() as u64;
+        let quota = Quota {
+            budget,
+            remaining: bucket.tokens.floor() as u32,
+            reset: seconds_until(capacity - bucket.tokens),
+        };
+        if allowed {
+            Ok(quota)
+        } else {
+            let retry_after = seconds_until(1.0 - bucket.tokens).max(1);
+            Err((quota, retry_after))
+        }
+    }
+}
+
+impl Quota {
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        let window = self.budget.window.as_secs();
+        headers.insert(RATELIMIT_LIMIT, HeaderValue::from(self.budget.capacity));
+        headers.insert(RATELIMIT_REMAINING, HeaderValue::from(self.remaining));
+        headers.insert(RATELIMIT_RESET, HeaderValue::from(self.reset));
+        if let Ok(policy) = HeaderValue::from_str(&format!("{};w={window}", self.budget.capacity)) {
+            headers.insert(RATELIMIT_POLICY, policy);
+        }
+    }
+}
+
+/// Middleware enforcing the write and search budgets.
+///
+/// Each client gets its own bucket per route pattern, so e.g. creating recipes
+/// does not use up the budget for tagging them. A deprecated unversioned route
+/// shares the bucket of its `/api/v1` replacement. Limited responses carry
+/// `RateLimit-Limit`, `RateLimit-Remaining`, `RateLimit-Reset` and
+/// `RateLimit-Policy` headers; requests over budget are answered with a 429
+/// and a `Retry-After` header without reaching the handler.
+///
+/// # Arguments
+///
+/// * `State(limiter)` - The rate limiter holding every client's buckets.
+/// * `State(jwt)` - The key used to identify clients by their bearer token.
+pub async fn limit(
+    State(limiter): State<RateLimiter>,
+    State(jwt): State<JwtKey>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limits) = limiter.0.as_deref() else {
+        return next.run(request).await;
+    };
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| canonical_route(path.as_str()))
+    else {
+        return next.run(request).await;
+    };
+    let Some(budget) = limits.budget_for(request.method(), &route) else {
+        return next.run(request).await;
+    };
+
+    let Some(client) = limits.client_key(&request, &jwt) else {
+        return next.run(request).await;
+    };
+    match limits.take((route, client), budget) {
+        Ok(quota) => {
+            let mut response = next.run(request).await;
+            quota.insert_headers(response.headers_mut());
+            response
+        }
+        Err((quota, retry_after)) => {
+            let mut response = ApiError::TooManyRequests { retry_after }.into_response();
+            quota.insert_headers(response.headers_mut());
+            response
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use entity::brewer as Brewer;
+use entity::recipe as Recipe;
+use entity::recipe_step as RecipeStep;
+use entity::roast as Roast;
+use entity::tag as Tag;
+use entity::tag_recipe as TagRecipe;
+use entity::user as User;
+use sea_orm::{DatabaseConnection, EntityTrait, LoaderTrait, QueryOrder};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::error::ApiError;
+use super::pagination::Paginated;
+use super::users::PartialUser;
+
+/// The `?expand=` query parameter accepted by the recipe read endpoints.
+///
+/// Takes a comma separated list of `brewer`, `roast`, `user`, `tags` and `steps`.
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Expand {
+    /// Comma separated relations to embed: `brewer`, `roast`, `user`, `tags` and `steps`.
+    pub expand: Option<String>,
+}
+
+/// The related data a request asked to embed.
+#[derive(Clone, Copy, Debug, Default)]
+struct Embeds {
+    brewer: bool,
+    roast: bool,
+    user: bool,
+    tags: bool,
+    steps: bool,
+}
+
+impl Expand {
+    fn embeds(&self) -> Result<Embeds, ApiError> {
+        let mut embeds = Embeds::default();
+        let names = self.expand.as_deref().unwrap_or_default().split(',');
+        for name in names.map(str::trim).filter(|name| !name.is_empty()) {
+            match name {
+                "brewer" => embeds.brewer = true,
+                "roast" => embeds.roast = true,
+                "user" => embeds.user = true,
+                "tags" => embeds.tags = true,
+                "steps" => embeds.steps = true,
+                other => {
+                    return Err(ApiError::BadRequest(format!(
+                        "cannot expand {other}, expected brewer, roast, user, tags or steps"
+                    )))
+                }
+            }
+        }
+        Ok(embeds)
+    }
+}
+
+/// A recipe with the related rows requested through [`Expand`].
+///
+/// The recipe fields are flattened into the top level object, so a detail with
+/// nothing expanded serializes exactly like a `Recipe::Model`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RecipeDetail {
+    #[serde(flatten)]
+    pub recipe: Recipe::Model,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brewer: Option<Brewer::Model>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roast: Option<Roast::Model>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<PartialUser>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<Tag::Model>>,
+    /// The pours, ordered by `position`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<RecipeStep::Model>>,
+}
+
+/// Loads the related rows requested by `expand` for every recipe in `recipes`.
+///
+/// Each requested relation is fetched with a single batched query no matter how
+/// many recipes there are.
+///
+/// # Errors
+///
+/// * `StatusCode::BAD_REQUEST` if `expand` names an unknown relation.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if a database query fails.
+pub async fn expand_recipes(
+    conn: &DatabaseConnection,
+    recipes: Vec<Recipe::Model>,
+    expand: &Expand,
+) -> Result<Vec<RecipeDetail>, ApiError> {
+    load_embeds(conn, recipes, expand.embeds()?).await
+}
+
+async fn load_embeds(
+    conn: &DatabaseConnection,
+    recipes: Vec<Recipe::Model>,
+    embeds: Embeds,
+) -> Result<Vec<RecipeDetail>, ApiError> {
+    let count = recipes.len();
+
+    let mut brewers = vec![None; count];
+    if embeds.brewer {
+        brewers = recipes.load_one(Brewer::Entity, conn).await?;
+    }
+    let mut roasts = vec![None; count];
+    if embeds.roast {
+        roasts = recipes.load_one(Roast::Entity, conn).await?;
+    }
+    let mut users = vec![None; count];
+    if embeds.user {
+        users = recipes
+            .load_one(User::Entity, conn)
+            .await?
+            .into_iter()
+            .map(|user| {
+                user.map(|user| PartialUser {
+                    id: user.id,
+                    username: user.username,
+                })
+            })
+            .collect();
+    }
+    let mut tags = vec![None; count];
+    if embeds.tags {
+        tags = recipes
+            .load_many_to_many(
+                Tag::Entity::find().order_by_asc(Tag::Column::Title),
+                TagRecipe::Entity,
+                conn,
+            )
+            .await?
+            .into_iter()
+            .map(Some)
+            .collect();
+    }
+    let mut steps = vec![None; count];
+    if embeds.steps {
+        steps = recipes
+            .load_many(
+                RecipeStep::Entity::find().order_by_asc(RecipeStep::Column::Position),
+                conn,
+            )
+            .await?
+            .into_iter()
+            .map(Some)
+            .collect();
+    }
+
+    let details = recipes
+        .into_iter()
+        .zip(brewers)
+        .zip(roasts)
+        .zip(users)
+        .zip(tags)
+        .zip(steps)
+        .map(
+            |(((((recipe, brewer), roast), user), tags), steps)| RecipeDetail {
+                recipe,
+                brewer,
+                roast,
+                user,
+                tags,
+                steps,
+            },
+        )
+        .collect();
+    Ok(details)
+}
+
+/// Expands a single recipe, see [`expand_recipes`].
+///
+/// The steps are always embedded, so a recipe fetched on its own can be brewed
+/// without opening the xBloom app.
+pub async fn expand_recipe(
+    conn: &DatabaseConnection,
+    recipe: Recipe::Model,
+    expand: &Expand,
+) -> Result<RecipeDetail, ApiError> {
+    let embeds = Embeds {
+        steps: true,
+        ..expand.embeds()?
+    };
+    let mut details = load_embeds(conn, vec![recipe], embeds).await?;
+    Ok(details.remove(0))
+}
+
+/// Expands every recipe in a page, see [`expand_recipes`].
+pub async fn expand_page(
+    conn: &DatabaseConnection,
+    page: Paginated<Recipe::Model>,
+    expand: &Expand,
+) -> Result<Paginated<RecipeDetail>, ApiError> {
+    Ok(Paginated {
+        items: expand_recipes(conn, page.items, expand).await?,
+        next_cursor: page.next_cursor,
+        total: page.total,
+    })
+}
@@ -1,14 +1,30 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-    Form,
-};
+use axum::{extract::State, http::StatusCode, response::Json};
 use entity::recipe as Recipe;
+use entity::recipe_step as RecipeStep;
+use entity::tag as Tag;
+use entity::tag_recipe as TagRecipe;
+use entity::user as User;
 
-use super::internal_error;
-use sea_orm::{entity::*, query::*, DatabaseConnection};
+use super::auth::{can_moderate, AuthUser};
+use super::error::{ApiError, ErrorBody};
+use super::extract::{FormOrJson, Path, Query};
+use super::metrics::{RECIPES_CREATED, RECIPE_SEARCHES};
+use super::pagination::{paginate, paginate_keyset, Paginated, Pagination};
+use super::recipe_detail::{expand_page, expand_recipe, Expand, RecipeDetail};
+use super::validation::validate_recipe;
+use super::xbloom::share_id;
+use chrono::Utc;
+use metrics::counter;
+use sea_orm::{
+    entity::*,
+    prelude::DateTime,
+    query::*,
+    sea_query::{Expr, Func, LikeExpr},
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, SqlErr,
+};
+use serde::Deserialize;
 use slug::slugify;
+use utoipa::{IntoParams, ToSchema};
 
 /// Asynchronously retrieves a list of recipes from the database.
 ///
@@ -19,27 +35,40 @@ use slug::slugify;
 /// # Arguments
 ///
 /// * `State(conn)`: A state containing the `DatabaseConnection` needed to execute the query.
+/// * `Query(page)`: The pagination parameters, see [`Pagination`].
+/// * `Query(expand)`: The related data to embed, see [`Expand`].
 ///
 /// # Returns
 ///
 /// This function returns a `Result` that, on success, contains a `Json` response
-/// with a vector of `Recipe::Model`. On failure, it returns a tuple containing
-/// a `StatusCode` and an error message.
+/// with one page of `Recipe::Model`. On failure, it returns an `ApiError`
+/// rendered as a JSON error response.
 ///
 /// # Errors
 ///
 /// This function might return the following errors:
 ///
-/// * `internal_error`: If there is an issue querying the database, the error is
+/// * `ApiError::Internal`: If there is an issue querying the database, the error is
 ///   mapped to an internal server error (`StatusCode::INTERNAL_SERVER_ERROR`).
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes",
+    tag = "recipes",
+    summary = "List recipes",
+    params(Pagination, Expand),
+    responses(
+        (status = 200, description = "One page of recipes", body = Paginated<RecipeDetail>),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_recipes(
     State(conn): State<DatabaseConnection>,
-) -> Result<Json<Vec<Recipe::Model>>, (StatusCode, String)> {
-    let recipes = Recipe::Entity::find()
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+    Query(page): Query<Pagination>,
+    Query(expand): Query<Expand>,
+) -> Result<Json<Paginated<RecipeDetail>>, ApiError> {
+    let recipes = paginate_recipes(&conn, Recipe::Entity::find(), &page, &expand).await?;
     Ok(Json(recipes))
 }
 
@@ -49,30 +78,45 @@ pub async fn get_recipes(
 ///
 /// - `State(conn)`: A shared state containing a database connection.
 /// - `Path(id)`: The ID of the recipe to fetch, extracted from the request path.
+/// - `Query(expand)`: The related data to embed, see [`Expand`].
 ///
 /// # Returns
 ///
-/// - `Result<Json<Option<Recipe::Model>>, (StatusCode, String)>`:
-///   - On success: A JSON object containing the recipe, wrapped in an `Option`.
-///       - If the recipe exists, returns `Some(recipe)`.
-///       - If the recipe does not exist, returns `None`.
-///   - On failure: An error tuple containing an HTTP status code and an error message.
+/// - `Result<Json<RecipeDetail>, ApiError>`:
+///   - On success: A JSON object containing the recipe.
+///   - On failure: An `ApiError` rendered as a JSON error response.
 ///
 /// # Errors
 ///
-/// This function will return an error tuple (`(StatusCode, String)`) in the following scenarios:
+/// This function will return an `ApiError` in the following scenarios:
+/// - The recipe does not exist (`StatusCode::NOT_FOUND`).
 /// - Database connection issues.
 /// - Internal server errors during query execution.
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/{id}",
+    tag = "recipes",
+    summary = "Get a recipe by id",
+    params(("id" = i32, Path, description = "Id of the recipe"), Expand),
+    responses(
+        (status = 200, description = "The recipe", body = RecipeDetail),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 404, description = "No recipe with this id", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_recipe_id(
     State(conn): State<DatabaseConnection>,
     Path(id): Path<i32>,
-) -> Result<Json<Option<Recipe::Model>>, (StatusCode, String)> {
+    Query(expand): Query<Expand>,
+) -> Result<Json<RecipeDetail>, ApiError> {
     let recipe = Recipe::Entity::find_by_id(id)
         .one(&conn)
-        .await
-        .map_err(internal_error)?;
-    Ok(Json(recipe))
+        .await?
+        .ok_or_else(recipe_not_found)?;
+    let detail = expand_recipe(&conn, recipe, &expand).await?;
+    Ok(Json(detail))
 }
 
 /// Asynchronously fetches a recipe based on a provided slug.
@@ -82,29 +126,45 @@ pub async fn get_recipe_id(
 /// * `State(conn)` - An instance of `State` holding a `DatabaseConnection`.
 ///   It is used to query the database for the recipe.
 /// * `Path(slug)` - A `Path` containing the slug of the recipe to retrieve.
+/// * `Query(expand)` - The related data to embed, see [`Expand`].
 ///
 /// # Returns
 ///
-/// * `Ok(Json(Some(recipe)))` - If a recipe matching the slug exists.
-/// * `Ok(Json(None))` - If no recipe matches the provided slug.
-/// * `Err((StatusCode, String))` - If there is an error fetching the recipe.
+/// * `Ok(Json(recipe))` - If a recipe matching the slug exists.
+/// * `Err(ApiError)` - If there is an error fetching the recipe.
 ///
 /// # Errors
 ///
-/// Returns a tuple `(StatusCode, String)` wrapped in an `Err` variant if:
+/// Returns an `ApiError` wrapped in an `Err` variant if:
 ///
+/// * No recipe matches the provided slug (`StatusCode::NOT_FOUND`).
 /// * There is an internal error when querying the database.
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/slug/{slug}",
+    tag = "recipes",
+    summary = "Get a recipe by slug",
+    params(("slug" = String, Path, description = "Slug of the recipe"), Expand),
+    responses(
+        (status = 200, description = "The recipe", body = RecipeDetail),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 404, description = "No recipe with this slug", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_recipe_slug(
     State(conn): State<DatabaseConnection>,
     Path(slug): Path<String>,
-) -> Result<Json<Option<Recipe::Model>>, (StatusCode, String)> {
+    Query(expand): Query<Expand>,
+) -> Result<Json<RecipeDetail>, ApiError> {
     let recipe = Recipe::Entity::find()
         .filter(Recipe::Column::Slug.eq(slug))
         .one(&conn)
-        .await
-        .map_err(internal_error)?;
-    Ok(Json(recipe))
+        .await?
+        .ok_or_else(recipe_not_found)?;
+    let detail = expand_recipe(&conn, recipe, &expand).await?;
+    Ok(Json(detail))
 }
 
 /// Fetches recipes with a specific title from the database.
@@ -116,25 +176,45 @@ pub async fn get_recipe_slug(
 ///
 /// - `State(conn)`: A `State` wrapper around the `DatabaseConnection` required to access the database.
 /// - `Path(title)`: A `Path` wrapper containing the title of the recipes to search for.
+/// - `Query(page)`: The pagination parameters, see [`Pagination`].
+/// - `Query(expand)`: The related data to embed, see [`Expand`].
 ///
 /// # Returns
 ///
-/// - `Ok(Json<Vec<Recipe::Model>>)` on success, containing a JSON array of recipe models.
-/// - `Err((StatusCode, String))` on failure, containing an HTTP status code and an error message.
+/// - `Ok(Json<Paginated<RecipeDetail>>)` on success, containing a page of recipe models.
+/// - `Err(ApiError)` on failure, rendered as a JSON error response.
 ///
 /// # Errors
 ///
 /// Returns an internal server error if the database query fails for any reason.
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/title/{title}",
+    tag = "recipes",
+    summary = "Find recipes by title",
+    params(("title" = String, Path, description = "Case-insensitive part of the title"), Pagination, Expand),
+    responses(
+        (status = 200, description = "Recipes whose title contains the text", body = Paginated<RecipeDetail>),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_recipe_title(
     State(conn): State<DatabaseConnection>,
     Path(title): Path<String>,
-) -> Result<Json<Vec<Recipe::Model>>, (StatusCode, String)> {
-    let recipes = Recipe::Entity::find()
-        .filter(Recipe::Column::Title.contains(title))
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+    Query(page): Query<Pagination>,
+    Query(expand): Query<Expand>,
+) -> Result<Json<Paginated<RecipeDetail>>, ApiError> {
+    let query = Recipe::Entity::find().filter(
+        Expr::expr(Func::lower(Expr::col((
+            Recipe::Entity,
+            Recipe::Column::Title,
+        ))))
+        .like(contains_pattern(&title.to_lowercase())),
+    );
+    let recipes = paginate_recipes(&conn, query, &page, &expand).await?;
     Ok(Json(recipes))
 }
 
@@ -144,29 +224,42 @@ pub async fn get_recipe_title(
 ///
 /// - `State(conn)`: Represents the database connection state.
 /// - `Path(roaster)`: The path parameter representing the roaster's name.
+/// - `Query(page)`: The pagination parameters, see [`Pagination`].
+/// - `Query(expand)`: The related data to embed, see [`Expand`].
 ///
 /// # Returns
 ///
-/// - `Result<Json<Vec<Recipe::Model>>, (StatusCode, String)>`: The function returns a `Result` containing either:
-///   - A JSON array of `Recipe::Model` on success.
-///   - A tuple `(StatusCode, String)` on failure.
+/// - `Result<Json<Paginated<RecipeDetail>>, ApiError>`: The function returns a `Result` containing either:
+///   - A page of `Recipe::Model` on success.
+///   - An `ApiError` on failure.
 ///
 /// # Detailed Description
 ///
 /// This function retrieves all recipes associated with a specific roaster from the database.
 /// It filters the `Recipe` entities based on the `roaster` name provided in the path parameter.
-/// The results are returned as a JSON array of `Recipe::Model`. If an error occurs during the database query,
+/// The results are returned as a page of `Recipe::Model`. If an error occurs during the database query,
 /// it maps the error to an internal server error and returns it.
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/roaster/{roaster}",
+    tag = "recipes",
+    summary = "List recipes by roaster",
+    params(("roaster" = String, Path, description = "Roaster name"), Pagination, Expand),
+    responses(
+        (status = 200, description = "Recipes from the roaster", body = Paginated<RecipeDetail>),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_recipes_roaster(
     State(conn): State<DatabaseConnection>,
     Path(roaster): Path<String>,
-) -> Result<Json<Vec<Recipe::Model>>, (StatusCode, String)> {
-    let recipes = Recipe::Entity::find()
-        .filter(Recipe::Column::Roaster.eq(roaster))
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+    Query(page): Query<Pagination>,
+    Query(expand): Query<Expand>,
+) -> Result<Json<Paginated<RecipeDetail>>, ApiError> {
+    let query = Recipe::Entity::find().filter(Recipe::Column::Roaster.eq(roaster));
+    let recipes = paginate_recipes(&conn, query, &page, &expand).await?;
     Ok(Json(recipes))
 }
 
@@ -179,30 +272,475 @@ pub async fn get_recipes_roaster(
 ///
 /// * `State(conn)` - The database connection state.
 /// * `Path(machine)` - The machine name provided as a path parameter.
+/// * `Query(page)` - The pagination parameters, see [`Pagination`].
+/// * `Query(expand)` - The related data to embed, see [`Expand`].
 ///
 /// # Returns
 ///
 /// This function returns a `Result`:
-/// * `Ok(Json<Vec<Recipe::Model>>)` - A JSON response containing a vector of recipes.
-/// * `Err((StatusCode, String))` - An error tuple containing the status code and error message.
+/// * `Ok(Json<Paginated<RecipeDetail>>)` - A JSON response containing a page of recipes.
+/// * `Err(ApiError)` - An error rendered as a JSON error response.
 ///
 /// # Errors
 ///
 /// Returns an error if the database query fails.
 /// The error is wrapped in an internal error function to provide a consistent error response.
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/machine/{machine}",
+    tag = "recipes",
+    summary = "List recipes by machine",
+    params(("machine" = String, Path, description = "Machine name"), Pagination, Expand),
+    responses(
+        (status = 200, description = "Recipes for the machine", body = Paginated<RecipeDetail>),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_recipes_machine(
     State(conn): State<DatabaseConnection>,
     Path(machine): Path<String>,
-) -> Result<Json<Vec<Recipe::Model>>, (StatusCode, String)> {
-    let recipes = Recipe::Entity::find()
-        .filter(Recipe::Column::Machine.eq(machine))
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+    Query(page): Query<Pagination>,
+    Query(expand): Query<Expand>,
+) -> Result<Json<Paginated<RecipeDetail>>, ApiError> {
+    let query = Recipe::Entity::find().filter(Recipe::Column::Machine.eq(machine));
+    let recipes = paginate_recipes(&conn, query, &page, &expand).await?;
     Ok(Json(recipes))
 }
 
+/// Sort orders accepted by [`search_recipes`].
+///
+/// Recipes are ordered by `created_at`, and by `id` among recipes created at the
+/// same time.
+#[derive(Clone, Copy, Debug, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RecipeSort {
+    /// Oldest recipes first.
+    #[default]
+    Oldest,
+    /// Newest recipes first.
+    Newest,
+}
+
+impl RecipeSort {
+    fn order(self) -> Order {
+        match self {
+            RecipeSort::Oldest => Order::Asc,
+            RecipeSort::Newest => Order::Desc,
+        }
+    }
+
+    /// Matches the recipes that come after `anchor` in this order.
+    fn after(self, anchor: &Recipe::Model) -> Condition {
+        let (created, id) = match self {
+            RecipeSort::Oldest => (
+                Recipe::Column::CreatedAt.gt(anchor.created_at),
+                Recipe::Column::Id.gt(anchor.id),
+            ),
+            RecipeSort::Newest => (
+                Recipe::Column::CreatedAt.lt(anchor.created_at),
+                Recipe::Column::Id.lt(anchor.id),
+            ),
+        };
+        Condition::any().add(created).add(
+            Condition::all()
+                .add(Recipe::Column::CreatedAt.eq(anchor.created_at))
+                .add(id),
+        )
+    }
+}
+
+/// Query parameters accepted by [`search_recipes`]. Every field is optional and
+/// all the given criteria must match.
+#[derive(Clone, Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecipeSearch {
+    /// Full text query over title, roaster, creator and tags. Words are matched
+    /// together, `word*` matches a prefix and `"two words"` matches a phrase.
+    /// On Postgres results are ranked by relevance, best match first, and `sort`
+    /// is ignored; other backends match each term as a case-insensitive substring
+    /// and keep the usual `sort` order.
+    pub q: Option<String>,
+    /// Case-insensitive substring of the title.
+    pub title: Option<String>,
+    pub roaster: Option<String>,
+    pub machine: Option<String>,
+    pub brewer_id: Option<i32>,
+    pub roast_id: Option<i32>,
+    pub user_id: Option<i32>,
+    pub creator: Option<String>,
+    pub temp: Option<String>,
+    /// Comma separated tag titles. A recipe must carry every listed tag.
+    pub tags: Option<String>,
+    /// Only recipes created at or after this time.
+    pub created_after: Option<DateTime>,
+    /// Only recipes created at or before this time.
+    pub created_before: Option<DateTime>,
+    #[serde(default)]
+    pub sort: RecipeSort,
+}
+
+impl RecipeSearch {
+    /// Builds the `Condition` matching every criterion set on the search.
+    pub fn condition(&self) -> Condition {
+        let mut cond = Condition::all();
+        if let Some(title) = &self.title {
+            cond = cond.add(
+                Expr::expr(Func::lower(Expr::col((
+                    Recipe::Entity,
+                    Recipe::Column::Title,
+                ))))
+                .like(contains_pattern(&title.to_lowercase())),
+            );
+        }
+        if let Some(roaster) = &self.roaster {
+            cond = cond.add(Recipe::Column::Roaster.eq(roaster));
+        }
+        if let Some(machine) = &self.machine {
+            cond = cond.add(Recipe::Column::Machine.eq(machine));
+        }
+        if let Some(brewer_id) = self.brewer_id {
+            cond = cond.add(Recipe::Column::BrewerId.eq(brewer_id));
+        }
+        if let Some(roast_id) = self.roast_id {
+            cond = cond.add(Recipe::Column::RoastId.eq(roast_id));
+        }
+        if let Some(user_id) = self.user_id {
+            cond = cond.add(Recipe::Column::UserId.eq(user_id));
+        }
+        if let Some(creator) = &self.creator {
+            cond = cond.add(Recipe::Column::Creator.eq(creator));
+        }
+        if let Some(temp) = &self.temp {
+            cond = cond.add(Recipe::Column::Temp.eq(temp));
+        }
+        for tag in self.tag_titles() {
+            let tagged = TagRecipe::Entity::find()
+                .select_only()
+                .column(TagRecipe::Column::RecipeId)
+                .inner_join(Tag::Entity)
+                .filter(
+                    Expr::expr(Func::lower(Expr::col((Tag::Entity, Tag::Column::Title))))
+                        .eq(tag.to_lowercase()),
+                )
+                .into_query();
+            cond = cond.add(Recipe::Column::Id.in_subquery(tagged));
+        }
+        if let Some(after) = self.created_after {
+            cond = cond.add(Recipe::Column::CreatedAt.gte(after));
+        }
+        if let Some(before) = self.created_before {
+            cond = cond.add(Recipe::Column::CreatedAt.lte(before));
+        }
+        cond
+    }
+
+    fn tag_titles(&self) -> impl Iterator<Item = &str> {
+        self.tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+    }
+}
+
+/// Searches recipes on any combination of criteria.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `Query(search)` - The search criteria, see [`RecipeSearch`].
+/// * `Query(page)` - The pagination parameters, see [`Pagination`].
+/// * `Query(expand)` - The related data to embed, see [`Expand`].
+///
+/// # Returns
+///
+/// * `Ok(Json<Paginated<RecipeDetail>>)` - A page of the recipes matching every criterion,
+///   ordered by relevance when `q` is given and by `search.sort` otherwise.
+/// * `Err(ApiError)` - If the database query fails.
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/search",
+    tag = "recipes",
+    summary = "Search recipes",
+    params(RecipeSearch, Pagination, Expand),
+    responses(
+        (status = 200, description = "Recipes matching every criterion", body = Paginated<RecipeDetail>),
+        (status = 400, description = "Unknown `expand` relation or cursor, or a page out of range", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn search_recipes(
+    State(conn): State<DatabaseConnection>,
+    Query(search): Query<RecipeSearch>,
+    Query(page): Query<Pagination>,
+    Query(expand): Query<Expand>,
+) -> Result<Json<Paginated<RecipeDetail>>, ApiError> {
+    let mut query = Recipe::Entity::find().filter(search.condition());
+
+    let terms = search.q.as_deref().map(query_terms).unwrap_or_default();
+    if !terms.is_empty() && conn.get_database_backend() == DbBackend::Postgres {
+        counter!(RECIPE_SEARCHES, "mode" => "full_text").increment(1);
+        let tsquery = to_tsquery(&terms);
+        // Rows after the cursor rank lower, or rank the same with a higher id.
+        let after = match page.cursor {
+            Some(cursor) => {
+                cursor_anchor(&conn, cursor).await?;
+                Some(Condition::all().add(Expr::cust_with_values(
+                    r#"(ts_rank("recipe"."search", to_tsquery('simple', $1)), -"recipe"."id")
+                        < (SELECT ts_rank("search", to_tsquery('simple', $2)), -"id"
+                           FROM "recipe" WHERE "id" = $3)"#,
+                    [
+                        Value::from(tsquery.clone()),
+                        Value::from(tsquery.clone()),
+                        Value::from(cursor),
+                    ],
+                )))
+            }
+            None => None,
+        };
+        let query = query
+            .filter(Expr::cust_with_values(
+                r#""recipe"."search" @@ to_tsquery('simple', $1)"#,
+                [tsquery.clone()],
+            ))
+            .order_by_desc(Expr::cust_with_values(
+                r#"ts_rank("recipe"."search", to_tsquery('simple', $1))"#,
+                [tsquery],
+            ))
+            .order_by_asc(Recipe::Column::Id);
+        let recipes = paginate_keyset(&conn, query, after, &page, |recipe: &Recipe::Model| {
+            recipe.id
+        })
+        .await?;
+        return Ok(Json(expand_page(&conn, recipes, &expand).await?));
+    }
+    if !terms.is_empty() {
+        counter!(RECIPE_SEARCHES, "mode" => "substring").increment(1);
+        query = query.filter(like_condition(&terms));
+    } else {
+        counter!(RECIPE_SEARCHES, "mode" => "filter").increment(1);
+    }
+
+    let after = match page.cursor {
+        Some(cursor) => Some(search.sort.after(&cursor_anchor(&conn, cursor).await?)),
+        None => None,
+    };
+    let query = query
+        .order_by(Recipe::Column::CreatedAt, search.sort.order())
+        .order_by(Recipe::Column::Id, search.sort.order());
+    let recipes = paginate_keyset(&conn, query, after, &page, |recipe: &Recipe::Model| {
+        recipe.id
+    })
+    .await?;
+    Ok(Json(expand_page(&conn, recipes, &expand).await?))
+}
+
+/// Looks up the recipe a search `?cursor=` points at.
+///
+/// # Errors
+///
+/// Returns `ApiError::BadRequest` if no recipe has the id `cursor`.
+async fn cursor_anchor(conn: &DatabaseConnection, cursor: i32) -> Result<Recipe::Model, ApiError> {
+    Recipe::Entity::find_by_id(cursor)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest(format!("unknown cursor {cursor}")))
+}
+
+/// One term of a `q` search: a single word or a double quoted phrase.
+struct QueryTerm<'a> {
+    words: Vec<&'a str>,
+    /// The term ended in `*` and matches any word starting with it.
+    prefix: bool,
+}
+
+/// Splits a user supplied search string into terms.
+///
+/// A double quoted run of words becomes one phrase term. Anything other than
+/// letters and digits is treated as a word separator, so user input can never
+/// produce a tsquery syntax error or a `LIKE` wildcard.
+fn query_terms(q: &str) -> Vec<QueryTerm<'_>> {
+    fn words(text: &str) -> Vec<&str> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect()
+    }
+
+    let mut terms = Vec::new();
+    for (i, part) in q.split('"').enumerate() {
+        if i % 2 == 1 {
+            // Inside quotes: the whole part is one phrase.
+            let phrase = words(part);
+            if !phrase.is_empty() {
+                terms.push(QueryTerm {
+                    words: phrase,
+                    prefix: false,
+                });
+            }
+            continue;
+        }
+        for token in part.split_whitespace() {
+            let token_words = words(token);
+            if !token_words.is_empty() {
+                terms.push(QueryTerm {
+                    words: token_words,
+                    prefix: token.ends_with('*'),
+                });
+            }
+        }
+    }
+    terms
+}
+
+/// Turns search terms into Postgres `to_tsquery` syntax, ANDing them together.
+fn to_tsquery(terms: &[QueryTerm]) -> String {
+    terms
+        .iter()
+        .map(|term| {
+            let mut query = term.words.join(" <-> ");
+            if term.prefix {
+                query.push_str(":*");
+            }
+            query
+        })
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+/// Matches every term as a case-insensitive substring of the title, roaster,
+/// creator or one of the recipe's tags. Used where full text search is not
+/// available.
+fn like_condition(terms: &[QueryTerm]) -> Condition {
+    let mut cond = Condition::all();
+    for term in terms {
+        let pattern = contains_pattern(&term.words.join(" ").to_lowercase());
+        let lower = |column: Recipe::Column| {
+            Expr::expr(Func::lower(Expr::col((Recipe::Entity, column)))).like(pattern.clone())
+        };
+        let tagged = TagRecipe::Entity::find()
+            .select_only()
+            .column(TagRecipe::Column::RecipeId)
+            .inner_join(Tag::Entity)
+            .filter(
+                Expr::expr(Func::lower(Expr::col((Tag::Entity, Tag::Column::Title))))
+                    .like(pattern.clone()),
+            )
+            .into_query();
+        cond = cond.add(
+            Condition::any()
+                .add(lower(Recipe::Column::Title))
+                .add(lower(Recipe::Column::Roaster))
+                .add(lower(Recipe::Column::Creator))
+                .add(Recipe::Column::Id.in_subquery(tagged)),
+        );
+    }
+    cond
+}
+
+/// A `LIKE` pattern matching any text that contains `text`.
+///
+/// `%` and `_` in `text` are escaped so they only match themselves.
+fn contains_pattern(text: &str) -> LikeExpr {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    LikeExpr::new(format!("%{escaped}%")).escape('\\')
+}
+
+/// Fetches all recipes carrying a tag. The tag title is matched ignoring case.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `Path(tag)` - The tag title provided as a path parameter.
+/// * `Query(page)` - The pagination parameters, see [`Pagination`].
+/// * `Query(expand)` - The related data to embed, see [`Expand`].
+///
+/// # Returns
+///
+/// * `Ok(Json<Paginated<RecipeDetail>>)` - A page of recipes with the tag.
+/// * `Err(ApiError)` - If the database query fails.
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/tag/{tag}",
+    tag = "recipes",
+    summary = "List recipes with a tag",
+    params(("tag" = String, Path, description = "Tag title, matched case-insensitively"), Pagination, Expand),
+    responses(
+        (status = 200, description = "Recipes carrying the tag", body = Paginated<RecipeDetail>),
+        (status = 400, description = "Unknown `expand` relation", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn get_recipes_tag(
+    State(conn): State<DatabaseConnection>,
+    Path(tag): Path<String>,
+    Query(page): Query<Pagination>,
+    Query(expand): Query<Expand>,
+) -> Result<Json<Paginated<RecipeDetail>>, ApiError> {
+    let search = RecipeSearch {
+        tags: Some(tag),
+        ..Default::default()
+    };
+    let query = Recipe::Entity::find().filter(search.condition());
+    let recipes = paginate_recipes(&conn, query, &page, &expand).await?;
+    Ok(Json(recipes))
+}
+
+/// The fields a client may set when creating or updating a recipe.
+///
+/// Everything else on a recipe (`id`, `slug`, `created_at` and the owning user) is
+/// filled in by the server.
+///
+/// `steps` can only be sent as JSON, url-encoded forms have no way to express a
+/// list of pours. When updating, leaving `steps` out keeps the stored pours.
+#[derive(Clone, Debug, PartialEq, Deserialize, ToSchema)]
+pub struct CreateRecipe {
+    pub title: String,
+    pub roaster: String,
+    pub temp: String,
+    pub link: String,
+    pub shop_link: String,
+    pub machine: String,
+    pub brewer_id: i32,
+    pub roast_id: i32,
+    /// Coffee dose, in grams.
+    #[serde(default)]
+    pub dose_grams: Option<f64>,
+    /// Grinder setting on the xBloom's built-in grinder.
+    #[serde(default)]
+    pub grind_size: Option<i32>,
+    /// Water to coffee ratio, e.g. `15.0` for 1:15.
+    #[serde(default)]
+    pub ratio: Option<f64>,
+    /// The pours, in the order they are made.
+    #[serde(default)]
+    pub steps: Option<Vec<CreateStep>>,
+}
+
+/// One pour of a recipe, as submitted by a client.
+///
+/// The position of the pour is taken from its place in [`CreateRecipe::steps`].
+#[derive(Clone, Debug, PartialEq, Deserialize, ToSchema)]
+pub struct CreateStep {
+    /// Water poured, in millilitres.
+    pub volume_ml: i32,
+    /// Water temperature, in degrees Celsius.
+    pub temperature_c: i32,
+    /// Flow rate, in millilitres per second.
+    pub flow_rate: f64,
+    /// Pour pattern: `center`, `circular` or `spiral`.
+    pub pattern: String,
+    /// Wait after the pour, in seconds.
+    #[serde(default)]
+    pub pause_secs: i32,
+}
+
 /// Asynchronously creates a new recipe in the database.
 ///
 /// # Arguments
@@ -210,25 +748,57 @@ pub async fn get_recipes_machine(
 /// * `State(conn): State<DatabaseConnection>`
 ///   - A managed state holding a [`DatabaseConnection`] used to perform database operations.
 ///
-/// * `form: Form<Recipe::Model>`
-///   - A form containing data for the recipe to be created.
+/// * `AuthUser(user): AuthUser`
+///   - The user the bearer token belongs to. `user_id`, `creator` and `oauth_user`
+///     are taken from this user rather than from the submitted form.
+///
+/// * `FormOrJson(input): FormOrJson<CreateRecipe>`
+///   - The recipe to create, sent either as a url-encoded form or as JSON.
+///     `created_at` is set to the current time.
 ///
 /// # Returns
 ///
-/// * `Result<Json<Option<Recipe::Model>>, (StatusCode, String)>`
-///   - On success, it returns a JSON response with the created recipe model.
-///   - On failure, it returns a tuple of `StatusCode` and an error message.
+/// * `Result<Json<RecipeDetail>, ApiError>`
+///   - On success, it returns a JSON response with the created recipe and its steps.
+///   - On failure, it returns an `ApiError`.
 ///
 /// # Errors
 ///
 /// This function can return an error if the insertion into the database fails.
-/// The error will be encapsulated in a tuple containing the HTTP status code and an error message string.
-
+/// The error will be returned as an `ApiError` and rendered as a JSON error response.
+/// A title that is already taken is rejected with `StatusCode::CONFLICT`, and a
+/// submission that fails [`validate_recipe`] with `StatusCode::UNPROCESSABLE_ENTITY`.
+/// A `link` to an xBloom recipe that was already shared is rejected with
+/// `StatusCode::CONFLICT` and an `ApiError::DuplicateRecipe` naming the existing slug.
+/// Requests without a valid bearer token are rejected with `StatusCode::UNAUTHORIZED`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/recipes",
+    tag = "recipes",
+    summary = "Create a recipe",
+    description = "The recipe is owned by the user the bearer token belongs to.",
+    request_body(content(
+        (CreateRecipe = "application/json"),
+        (CreateRecipe = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The created recipe with its steps", body = RecipeDetail),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 409, description = "The title is taken, or the xBloom recipe was already shared", body = ErrorBody),
+        (status = 422, description = "Malformed body or invalid fields", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn create_recipe(
     State(conn): State<DatabaseConnection>,
-    form: Form<Recipe::Model>,
-) -> Result<Json<Option<Recipe::Model>>, (StatusCode, String)> {
-    let form = form.0;
+    AuthUser(user): AuthUser,
+    FormOrJson(form): FormOrJson<CreateRecipe>,
+) -> Result<Json<RecipeDetail>, ApiError> {
+    validate_recipe(&conn, &form, &user.username).await?;
+    let share_id = share_id(&form.link);
+    reject_duplicate(&conn, share_id.as_deref(), None).await?;
     let slug = slugify(&form.title);
     let recipe = Recipe::ActiveModel {
         id: Default::default(),
@@ -239,14 +809,411 @@ pub async fn create_recipe(
         link: ActiveValue::set(form.link),
         shop_link: ActiveValue::set(form.shop_link),
         machine: ActiveValue::set(form.machine),
-        creator: ActiveValue::set(form.creator),
-        oauth_user: ActiveValue::set(form.oauth_user),
-        user_id: ActiveValue::set(form.user_id),
+        creator: ActiveValue::set(user.username.clone()),
+        oauth_user: ActiveValue::set(Some(user.username)),
+        user_id: ActiveValue::set(user.id),
         brewer_id: ActiveValue::set(form.brewer_id),
         roast_id: ActiveValue::set(form.roast_id),
-        created_at: ActiveValue::set(form.created_at),
+        created_at: ActiveValue::set(Utc::now().naive_utc()),
+        dose_grams: ActiveValue::set(form.dose_grams),
+        grind_size: ActiveValue::set(form.grind_size),
+        ratio: ActiveValue::set(form.ratio),
+        share_id: ActiveValue::set(share_id.clone()),
+    };
+
+    let txn = conn.begin().await?;
+    let result = match recipe.insert(&txn).await {
+        Ok(result) => result,
+        Err(err) => {
+            txn.rollback().await?;
+            return Err(unique_conflict(&conn, err, share_id.as_deref(), None).await);
+        }
+    };
+    if let Some(steps) = form.steps {
+        insert_steps(&txn, result.id, steps).await?;
+    }
+    txn.commit().await?;
+    counter!(RECIPES_CREATED).increment(1);
+
+    let detail = expand_recipe(&conn, result, &Expand::default()).await?;
+    Ok(Json(detail))
+}
+
+/// Asynchronously updates an existing recipe.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `AuthUser(user)` - The user the bearer token belongs to. Only the user that owns the
+///   recipe, or a moderator, may update it.
+/// * `Path(id)` - The ID of the recipe to update.
+/// * `FormOrJson(form)` - The new values for the recipe, as a form or as JSON. `id`,
+///   `user_id`, `creator`, `oauth_user` and `created_at` are kept from the stored recipe.
+///
+/// # Returns
+///
+/// * `Ok(Json(recipe))` - The updated recipe and its steps. The `slug` is regenerated from
+///   the new title, and the steps are replaced when the form includes `steps`.
+/// * `Err(ApiError)` - If the recipe could not be updated.
+///
+/// # Errors
+///
+/// * `StatusCode::NOT_FOUND` if no recipe has the given ID.
+/// * `StatusCode::FORBIDDEN` if the authenticated user neither owns the recipe nor is a moderator.
+/// * `StatusCode::UNPROCESSABLE_ENTITY` if the new values fail [`validate_recipe`].
+/// * `StatusCode::CONFLICT` if the new title is already taken, or if the new `link` points
+///   at an xBloom recipe another recipe already shares.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if the database update fails.
+#[utoipa::path(
+    put,
+    path = "/api/v1/recipes/{id}",
+    tag = "recipes",
+    summary = "Update a recipe",
+    description = "Only the owner, moderators and admins may update a recipe.",
+    params(("id" = i32, Path, description = "Id of the recipe")),
+    request_body(content(
+        (CreateRecipe = "application/json"),
+        (CreateRecipe = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated recipe with its steps", body = RecipeDetail),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not the owner or a moderator", body = ErrorBody),
+        (status = 404, description = "No recipe with this id", body = ErrorBody),
+        (status = 409, description = "The title is taken, or the xBloom recipe was already shared", body = ErrorBody),
+        (status = 422, description = "Malformed body or invalid fields", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn update_recipe(
+    State(conn): State<DatabaseConnection>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i32>,
+    FormOrJson(form): FormOrJson<CreateRecipe>,
+) -> Result<Json<RecipeDetail>, ApiError> {
+    let existing = find_owned_recipe(&conn, &user, id).await?;
+    let detail = save_recipe(&conn, existing, form).await?;
+    Ok(Json(detail))
+}
+
+/// The fields a client may change when partially updating a recipe.
+///
+/// Every field is optional and fields left out keep their stored value. A
+/// `null` is treated like a missing field, so `dose_grams`, `grind_size` and
+/// `ratio` can only be cleared with a full update.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, ToSchema)]
+pub struct PatchRecipe {
+    pub title: Option<String>,
+    pub roaster: Option<String>,
+    pub temp: Option<String>,
+    pub link: Option<String>,
+    pub shop_link: Option<String>,
+    pub machine: Option<String>,
+    pub brewer_id: Option<i32>,
+    pub roast_id: Option<i32>,
+    pub dose_grams: Option<f64>,
+    pub grind_size: Option<i32>,
+    pub ratio: Option<f64>,
+    /// Replaces every pour when present.
+    pub steps: Option<Vec<CreateStep>>,
+}
+
+impl PatchRecipe {
+    /// The full set of values `existing` would have with this patch applied.
+    fn apply_to(self, existing: &Recipe::Model) -> CreateRecipe {
+        CreateRecipe {
+            title: self.title.unwrap_or_else(|| existing.title.clone()),
+            roaster: self.roaster.unwrap_or_else(|| existing.roaster.clone()),
+            temp: self.temp.unwrap_or_else(|| existing.temp.clone()),
+            link: self.link.unwrap_or_else(|| existing.link.clone()),
+            shop_link: self.shop_link.unwrap_or_else(|| existing.shop_link.clone()),
+            machine: self.machine.unwrap_or_else(|| existing.machine.clone()),
+            brewer_id: self.brewer_id.unwrap_or(existing.brewer_id),
+            roast_id: self.roast_id.unwrap_or(existing.roast_id),
+            dose_grams: self.dose_grams.or(existing.dose_grams),
+            grind_size: self.grind_size.or(existing.grind_size),
+            ratio: self.ratio.or(existing.ratio),
+            steps: self.steps,
+        }
+    }
+}
+
+/// Asynchronously applies a partial update to an existing recipe.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `AuthUser(user)` - The user the bearer token belongs to. Only the user that owns the
+///   recipe, or a moderator, may update it.
+/// * `Path(id)` - The ID of the recipe to update.
+/// * `FormOrJson(patch)` - The fields to change, as a form or as JSON. Fields left out
+///   keep their stored value.
+///
+/// # Returns
+///
+/// * `Ok(Json(recipe))` - The updated recipe and its steps. The `slug` is regenerated
+///   when the title changes.
+/// * `Err(ApiError)` - If the recipe could not be updated.
+///
+/// # Errors
+///
+/// The same as [`update_recipe`]; validation runs on the patched recipe as a whole.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/recipes/{id}",
+    tag = "recipes",
+    summary = "Partially update a recipe",
+    description = "Only the fields sent are changed. Only the owner, moderators and admins may update a recipe.",
+    params(("id" = i32, Path, description = "Id of the recipe")),
+    request_body(content(
+        (PatchRecipe = "application/json"),
+        (PatchRecipe = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated recipe with its steps", body = RecipeDetail),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not the owner or a moderator", body = ErrorBody),
+        (status = 404, description = "No recipe with this id", body = ErrorBody),
+        (status = 409, description = "The title is taken, or the xBloom recipe was already shared", body = ErrorBody),
+        (status = 422, description = "Malformed body or invalid fields", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn patch_recipe(
+    State(conn): State<DatabaseConnection>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i32>,
+    FormOrJson(patch): FormOrJson<PatchRecipe>,
+) -> Result<Json<RecipeDetail>, ApiError> {
+    let existing = find_owned_recipe(&conn, &user, id).await?;
+    let form = patch.apply_to(&existing);
+    let detail = save_recipe(&conn, existing, form).await?;
+    Ok(Json(detail))
+}
+
+/// Validates `form` and stores it over `existing`, replacing the steps when the
+/// form includes them.
+async fn save_recipe(
+    conn: &DatabaseConnection,
+    existing: Recipe::Model,
+    form: CreateRecipe,
+) -> Result<RecipeDetail, ApiError> {
+    validate_recipe(conn, &form, &existing.creator).await?;
+    let share_id = share_id(&form.link);
+    let id = existing.id;
+    reject_duplicate(conn, share_id.as_deref(), Some(id)).await?;
+
+    let mut recipe: Recipe::ActiveModel = existing.into();
+    recipe.slug = ActiveValue::set(slugify(&form.title));
+    recipe.title = ActiveValue::set(form.title);
+    recipe.roaster = ActiveValue::set(form.roaster);
+    recipe.temp = ActiveValue::set(form.temp);
+    recipe.link = ActiveValue::set(form.link);
+    recipe.shop_link = ActiveValue::set(form.shop_link);
+    recipe.machine = ActiveValue::set(form.machine);
+    recipe.brewer_id = ActiveValue::set(form.brewer_id);
+    recipe.roast_id = ActiveValue::set(form.roast_id);
+    recipe.dose_grams = ActiveValue::set(form.dose_grams);
+    recipe.grind_size = ActiveValue::set(form.grind_size);
+    recipe.ratio = ActiveValue::set(form.ratio);
+    recipe.share_id = ActiveValue::set(share_id.clone());
+
+    let txn = conn.begin().await?;
+    let result = match recipe.update(&txn).await {
+        Ok(result) => result,
+        Err(err) => {
+            txn.rollback().await?;
+            return Err(unique_conflict(conn, err, share_id.as_deref(), Some(id)).await);
+        }
+    };
+    if let Some(steps) = form.steps {
+        RecipeStep::Entity::delete_many()
+            .filter(RecipeStep::Column::RecipeId.eq(result.id))
+            .exec(&txn)
+            .await?;
+        insert_steps(&txn, result.id, steps).await?;
+    }
+    txn.commit().await?;
+
+    expand_recipe(conn, result, &Expand::default()).await
+}
+
+/// Asynchronously deletes a recipe along with its steps and the tags attached to it.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `AuthUser(user)` - The user the bearer token belongs to. Only the user that owns the
+///   recipe, or a moderator, may delete it.
+/// * `Path(id)` - The ID of the recipe to delete.
+///
+/// # Returns
+///
+/// * `Ok(StatusCode::NO_CONTENT)` - The recipe and its `recipe_step` and `tag_recipe` rows
+///   were removed.
+/// * `Err(ApiError)` - If the recipe could not be deleted.
+///
+/// # Errors
+///
+/// * `StatusCode::NOT_FOUND` if no recipe has the given ID.
+/// * `StatusCode::FORBIDDEN` if the authenticated user neither owns the recipe nor is a moderator.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if the database delete fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/recipes/{id}",
+    tag = "recipes",
+    summary = "Delete a recipe",
+    description = "Only the owner, moderators and admins may delete a recipe.",
+    params(("id" = i32, Path, description = "Id of the recipe")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Deleted along with its steps and tag links"),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not the owner or a moderator", body = ErrorBody),
+        (status = 404, description = "No recipe with this id", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn delete_recipe(
+    State(conn): State<DatabaseConnection>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let recipe = find_owned_recipe(&conn, &user, id).await?;
+
+    let txn = conn.begin().await?;
+    TagRecipe::Entity::delete_many()
+        .filter(TagRecipe::Column::RecipeId.eq(recipe.id))
+        .exec(&txn)
+        .await?;
+    RecipeStep::Entity::delete_many()
+        .filter(RecipeStep::Column::RecipeId.eq(recipe.id))
+        .exec(&txn)
+        .await?;
+    recipe.delete(&txn).await?;
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Returns one page of the recipes matched by `query`, with the relations in `expand` embedded.
+async fn paginate_recipes(
+    conn: &DatabaseConnection,
+    query: Select<Recipe::Entity>,
+    page: &Pagination,
+    expand: &Expand,
+) -> Result<Paginated<RecipeDetail>, ApiError> {
+    let recipes = paginate(
+        conn,
+        query,
+        Recipe::Column::Id,
+        page,
+        |recipe: &Recipe::Model| recipe.id,
+    )
+    .await?;
+    expand_page(conn, recipes, expand).await
+}
+
+/// Stores `steps` as the pours of the recipe `recipe_id`, numbered in list order.
+async fn insert_steps(
+    conn: &impl ConnectionTrait,
+    recipe_id: i32,
+    steps: Vec<CreateStep>,
+) -> Result<(), DbErr> {
+    if steps.is_empty() {
+        return Ok(());
+    }
+    let rows = steps
+        .into_iter()
+        .zip(0..)
+        .map(|(step, position)| RecipeStep::ActiveModel {
+            id: Default::default(),
+            recipe_id: ActiveValue::set(recipe_id),
+            position: ActiveValue::set(position),
+            volume_ml: ActiveValue::set(step.volume_ml),
+            temperature_c: ActiveValue::set(step.temperature_c),
+            flow_rate: ActiveValue::set(step.flow_rate),
+            pattern: ActiveValue::set(step.pattern),
+            pause_secs: ActiveValue::set(step.pause_secs),
+        });
+    RecipeStep::Entity::insert_many(rows).exec(conn).await?;
+    Ok(())
+}
+
+/// Fails with `ApiError::DuplicateRecipe` if a recipe other than `exclude` already
+/// has the share id `share_id`.
+async fn reject_duplicate(
+    conn: &DatabaseConnection,
+    share_id: Option<&str>,
+    exclude: Option<i32>,
+) -> Result<(), ApiError> {
+    let Some(share_id) = share_id else {
+        return Ok(());
     };
+    let mut query = Recipe::Entity::find().filter(Recipe::Column::ShareId.eq(share_id));
+    if let Some(id) = exclude {
+        query = query.filter(Recipe::Column::Id.ne(id));
+    }
+    match query.one(conn).await? {
+        Some(existing) => Err(ApiError::DuplicateRecipe {
+            slug: existing.slug,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The error returned when a recipe lookup finds nothing.
+pub(crate) fn recipe_not_found() -> ApiError {
+    ApiError::NotFound("recipe not found".to_string())
+}
+
+/// Reports a unique violation from storing a recipe as a 409.
+///
+/// A violation on `share_id` means another request shared the same xBloom recipe
+/// after [`reject_duplicate`] ran, and is reported as `ApiError::DuplicateRecipe`
+/// naming that recipe. A violation on `title` or `slug` names the title. Any
+/// transaction the failed statement ran in must be closed first, as the lookup
+/// uses `conn`.
+async fn unique_conflict(
+    conn: &DatabaseConnection,
+    err: DbErr,
+    share_id: Option<&str>,
+    exclude: Option<i32>,
+) -> ApiError {
+    match err.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(message)) if message.contains("share_id") => {
+            match reject_duplicate(conn, share_id, exclude).await {
+                Err(duplicate) => duplicate,
+                Ok(()) => ApiError::Conflict("this xBloom recipe was already shared".to_string()),
+            }
+        }
+        Some(SqlErr::UniqueConstraintViolation(_)) => {
+            ApiError::Conflict("a recipe with this title already exists".to_string())
+        }
+        _ => err.into(),
+    }
+}
+
+/// Looks up a recipe by ID and checks that `user` is allowed to modify it.
+pub(crate) async fn find_owned_recipe(
+    conn: &DatabaseConnection,
+    user: &User::Model,
+    id: i32,
+) -> Result<Recipe::Model, ApiError> {
+    let recipe = Recipe::Entity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(recipe_not_found)?;
 
-    let result = recipe.insert(&conn).await.map_err(internal_error)?;
-    Ok(Json(Some(result)))
+    if recipe.user_id != user.id && !can_moderate(user) {
+        return Err(ApiError::Forbidden(
+            "only the owner can modify this recipe".to_string(),
+        ));
+    }
+    Ok(recipe)
 }
@@ -1,41 +1,57 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-};
+use axum::{extract::State, http::StatusCode, response::Json};
+use entity::recipe as Recipe;
 use entity::roast as Roast;
 
-use super::internal_error;
-use sea_orm::{entity::*, DatabaseConnection};
+use super::auth::AdminUser;
+use super::error::{ApiError, ErrorBody};
+use super::extract::{FormOrJson, Path, Query};
+use super::pagination::{paginate, Paginated, Pagination};
+use sea_orm::{entity::*, query::*, DatabaseConnection};
 
 /// Retrieves a list of roasts from the database.
 ///
 /// This asynchronous function interacts with a database connection provided via the `State`
-/// wrapper and returns a JSON response containing a vector of `Roast::Model`. In case of an
-/// error, it returns an appropriate `StatusCode` and error message.
+/// wrapper and returns a JSON response containing one page of `Roast::Model`. In case of an
+/// error, it returns an `ApiError` rendered as a JSON error response.
 ///
 /// # Parameters
 ///
 /// - `State(conn)`: A state wrapper containing a `DatabaseConnection` which is used to query the database.
+/// - `Query(page)`: The pagination parameters, see [`Pagination`].
 ///
 /// # Returns
 ///
-/// - `Result<Json<Vec<Roast::Model>>, (StatusCode, String)>`:
-///     - On success, it returns `Ok(Json(roasts))`, where `roasts` is a vector of `Roast::Model` instances retrieved from the database.
-///     - On failure, it returns an error tuple containing a `StatusCode` and a descriptive error message.
+/// - `Result<Json<Paginated<Roast::Model>>, ApiError>`:
+///     - On success, it returns `Ok(Json(roasts))`, where `roasts` is a page of `Roast::Model` instances retrieved from the database.
+///     - On failure, it returns an `ApiError` with a descriptive error message.
 ///
 /// # Errors
 ///
 /// This function may return the following errors:
-/// - `(StatusCode::INTERNAL_SERVER_ERROR, String::from("Error message"))` if there is an error while querying the database.
-
+/// - `ApiError::Internal` if there is an error while querying the database.
+#[utoipa::path(
+    get,
+    path = "/api/v1/roasts",
+    tag = "roasts",
+    summary = "List roasts",
+    params(Pagination),
+    responses(
+        (status = 200, description = "One page of roasts", body = Paginated<Roast::Model>),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_roasts(
     State(conn): State<DatabaseConnection>,
-) -> Result<Json<Vec<Roast::Model>>, (StatusCode, String)> {
-    let roasts = Roast::Entity::find()
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+    Query(page): Query<Pagination>,
+) -> Result<Json<Paginated<Roast::Model>>, ApiError> {
+    let roasts = paginate(
+        &conn,
+        Roast::Entity::find(),
+        Roast::Column::Id,
+        &page,
+        |roast: &Roast::Model| roast.id,
+    )
+    .await?;
     Ok(Json(roasts))
 }
 
@@ -48,24 +64,179 @@ pub async fn get_roasts(
 ///
 /// # Returns
 ///
-/// * `Result<Json<Option<Roast::Model>>, (StatusCode, String)>` - A JSON response containing
-///   either the `Roast` model if found, or `None` if not found. In case of an error, it returns
-///   a tuple with an HTTP status code and an error message.
+/// * `Result<Json<Roast::Model>, ApiError>` - A JSON response containing the `Roast` model
+///   if found. In case of an error, it returns an `ApiError` rendered as a JSON error response.
 ///
 /// # Errors
 ///
-/// This function can return an error in the form of `(StatusCode, String)` if:
+/// This function can return an `ApiError` if:
 ///
-/// * There is an issue interacting with the database.
-/// * There is an internal error during the `find_by_id` operation.
+/// * No roast has the given ID (`ApiError::NotFound`).
+/// * The `find_by_id` query fails (`ApiError::Internal`).
 ///
+#[utoipa::path(
+    get,
+    path = "/api/v1/roasts/{id}",
+    tag = "roasts",
+    summary = "Get a roast",
+    params(("id" = i32, Path, description = "Id of the roast")),
+    responses(
+        (status = 200, description = "The roast", body = Roast::Model),
+        (status = 404, description = "No roast with this id", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_roast_id(
     State(conn): State<DatabaseConnection>,
     Path(id): Path<i32>,
-) -> Result<Json<Option<Roast::Model>>, (StatusCode, String)> {
-    let roast = Roast::Entity::find_by_id(id)
-        .one(&conn)
-        .await
-        .map_err(internal_error)?;
+) -> Result<Json<Roast::Model>, ApiError> {
+    let roast = find_roast(&conn, id).await?;
     Ok(Json(roast))
 }
+
+/// Creates a new roast. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `FormOrJson(form)` - The roast to create, as a form or as JSON.
+///
+/// # Returns
+///
+/// * `Ok(Json(roast))` - The newly created roast.
+/// * `Err(ApiError)` - If the roast could not be created.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/roasts",
+    tag = "roasts",
+    summary = "Create a roast",
+    description = "Only available to admins. Only `level` is read from the body.",
+    request_body(content(
+        (Roast::Model = "application/json"),
+        (Roast::Model = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The created roast", body = Roast::Model),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn create_roast(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    FormOrJson(form): FormOrJson<Roast::Model>,
+) -> Result<Json<Roast::Model>, ApiError> {
+    let roast = Roast::ActiveModel {
+        id: Default::default(),
+        level: ActiveValue::set(form.level),
+    };
+    let result = roast.insert(&conn).await?;
+    Ok(Json(result))
+}
+
+/// Renames an existing roast. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `Path(id)` - The ID of the roast to rename.
+/// * `FormOrJson(form)` - The new roast level, as a form or as JSON.
+///
+/// # Errors
+///
+/// * `ApiError::NotFound` if no roast has the given ID.
+/// * `ApiError::Internal` if the database update fails.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/roasts/{id}",
+    tag = "roasts",
+    summary = "Update a roast",
+    description = "Only available to admins. Only `level` is read from the body.",
+    params(("id" = i32, Path, description = "Id of the roast")),
+    request_body(content(
+        (Roast::Model = "application/json"),
+        (Roast::Model = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated roast", body = Roast::Model),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "No roast with this id", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn update_roast(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    Path(id): Path<i32>,
+    FormOrJson(form): FormOrJson<Roast::Model>,
+) -> Result<Json<Roast::Model>, ApiError> {
+    let mut roast: Roast::ActiveModel = find_roast(&conn, id).await?.into();
+    roast.level = ActiveValue::set(form.level);
+    let result = roast.update(&conn).await?;
+    Ok(Json(result))
+}
+
+/// Deletes a roast. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `Path(id)` - The ID of the roast to delete.
+///
+/// # Errors
+///
+/// * `ApiError::NotFound` if no roast has the given ID.
+/// * `ApiError::Conflict` if any recipe still uses the roast.
+/// * `ApiError::Internal` if the database delete fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/roasts/{id}",
+    tag = "roasts",
+    summary = "Delete a roast",
+    description = "Only available to admins.",
+    params(("id" = i32, Path, description = "Id of the roast")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "No roast with this id", body = ErrorBody),
+        (status = 409, description = "Recipes still use the roast", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn delete_roast(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let roast = find_roast(&conn, id).await?;
+    let in_use = Recipe::Entity::find()
+        .filter(Recipe::Column::RoastId.eq(roast.id))
+        .count(&conn)
+        .await?;
+    if in_use > 0 {
+        return Err(ApiError::Conflict(format!(
+            "roast is used by {in_use} recipe(s)"
+        )));
+    }
+    roast.delete(&conn).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_roast(conn: &DatabaseConnection, id: i32) -> Result<Roast::Model, ApiError> {
+    Roast::Entity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("roast not found".to_string()))
+}
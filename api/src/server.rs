@@ -1,46 +1,308 @@
 use axum::{
-    http::StatusCode,
+    handler::Handler,
+    http::Method,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, on, post, put, MethodFilter, MethodRouter},
     Json, Router,
 };
 
+use crate::auth::JwtKey;
 use crate::brewers;
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::health;
+use crate::metrics;
+use crate::openapi;
+use crate::rate_limit::{self, RateLimiter};
 use crate::recipes;
 use crate::recipes::get_recipe_id;
 use crate::roasts;
+use crate::tags;
 use crate::users;
 use axum::body::Body;
 use axum::error_handling::HandleErrorLayer;
-use axum::http::Response;
+use axum::extract::{FromRef, Request};
+use axum::http::{header, HeaderName, HeaderValue, Response};
+use axum::middleware::{self, Next};
+use metrics_exporter_prometheus::PrometheusHandle;
 use migration::{Migrator, MigratorTrait};
-use sea_orm::Database;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
 use serde_json::json;
-use std::env;
-use std::time::Duration;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::signal;
+use tokio::sync::oneshot;
 use tower::{BoxError, ServiceBuilder};
-use tower_http::cors::CorsLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tower_http::trace::TraceLayer;
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn start() -> anyhow::Result<()> {
-    env::set_var("RUST_LOG", "debug");
-    tracing_subscriber::fmt::init();
+    let config = Config::load()?;
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(&config.log_level))
+        .init();
 
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set in .env file");
-    let host = env::var("HOST").expect("HOST is not set in .env file");
-    let port = env::var("PORT").expect("PORT is not set in .env file");
-    let server_url = format!("{host}:{port}");
+    let mut options = ConnectOptions::new(config.database_url.clone());
+    options.max_connections(config.db_max_connections);
+    let conn = Database::connect(options).await?;
 
-    let conn = Database::connect(db_url)
-        .await
-        .expect("Database connection failed");
+    if config.auto_migrate {
+        Migrator::up(&conn, None).await?;
+    }
+
+    let address = config.bind_address();
+    let drain_timeout = config.shutdown_timeout();
+    let app = app(AppState::new(conn.clone(), config));
+
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    tracing::debug!("listening on {}", listener.local_addr().unwrap());
+
+    // Once a shutdown signal arrives the server stops accepting connections and
+    // waits for in-flight requests, but never longer than the drain timeout.
+    let (draining_tx, draining_rx) = oneshot::channel();
+    // The peer address identifies anonymous clients for rate limiting.
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        tracing::info!("shutting down, draining in-flight requests");
+        draining_tx.send(()).ok();
+    });
+    let drain_deadline = async move {
+        match draining_rx.await {
+            Ok(()) => tokio::time::sleep(drain_timeout).await,
+            // The server stopped without a signal; let its result win.
+            Err(_) => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        result = server => result?,
+        () = drain_deadline => {
+            tracing::warn!("requests still in flight after {drain_timeout:?}, stopping anyway");
+        }
+    }
+
+    conn.close().await?;
+    tracing::info!("database connections closed");
+
+    Ok(())
+}
+
+/// Shared state handed to every handler.
+///
+/// Handlers extract the parts they need, e.g. `State<DatabaseConnection>`,
+/// through the derived `FromRef` impls.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub conn: DatabaseConnection,
+    pub jwt: JwtKey,
+    pub config: Arc<Config>,
+    pub metrics: PrometheusHandle,
+    pub rate_limiter: RateLimiter,
+}
+
+impl AppState {
+    /// Builds the state from an open connection and the loaded configuration.
+    ///
+    /// The JWT key is taken from `config.jwt_secret`, the rate limiter from the
+    /// `rate_limit_*` settings, and `conn` is instrumented to report query
+    /// latencies to the process wide metrics recorder. `/metrics` is served from
+    /// [`metrics::handle`], which installs the bloomdb recorder unless another one
+    /// is already installed.
+    /// Migrations are not run; callers that need them should call `Migrator::up` first.
+    pub fn new(conn: DatabaseConnection, config: Config) -> Self {
+        AppState::with_metrics(conn, config, metrics::handle())
+    }
+
+    /// Like [`AppState::new`], but serves `/metrics` from `handle`, a handle to a
+    /// Prometheus recorder the caller installed, e.g. one built with
+    /// [`metrics::builder`]. No recorder is installed.
+    pub fn with_metrics(
+        mut conn: DatabaseConnection,
+        config: Config,
+        handle: PrometheusHandle,
+    ) -> Self {
+        metrics::instrument_connection(&mut conn);
+        AppState {
+            conn,
+            jwt: JwtKey::new(config.jwt_secret.as_deref()),
+            rate_limiter: RateLimiter::new(&config),
+            config: Arc::new(config),
+            metrics: handle,
+        }
+    }
+}
+
+/// Builds the complete bloomdb router: every route, the rate limit, timeout,
+/// tracing, metrics and CORS layers, and the JSON 404 fallback.
+///
+/// The documented routes come from [`endpoints`] and are served under `/api/v1`.
+/// The unversioned paths they replaced are still routed, see [`legacy_routes`].
+///
+/// Nothing is bound or migrated, so the router can be driven directly with
+/// `tower::ServiceExt::oneshot` or nested inside another axum application.
+/// Serve it with `into_make_service_with_connect_info::<SocketAddr>()`: the
+/// rate limiter identifies anonymous clients by their address and does not
+/// limit them without one.
+pub fn app(state: AppState) -> Router {
+    let mut router = Router::new()
+        .route("/api/v1/openapi.json", get(openapi::openapi_json))
+        .route("/api/v1/docs", get(openapi::docs));
+    for endpoint in endpoints() {
+        // Routes added for the same path are merged into one method router.
+        router = router.route(endpoint.path, endpoint.handler);
+    }
+    router
+        .merge(legacy_routes())
+        .fallback(handler_404)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit,
+        ))
+        // Add middleware to all routes
+        .layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(|error: BoxError| async move {
+                    if error.is::<tower::timeout::error::Elapsed>() {
+                        ApiError::Timeout
+                    } else {
+                        ApiError::Internal(format!("Unhandled internal error: {error}"))
+                    }
+                }))
+                .timeout(state.config.request_timeout())
+                .layer(TraceLayer::new_for_http())
+                .into_inner(),
+        )
+        .layer(middleware::from_fn(metrics::track_requests))
+        .layer(cors_layer(&state.config))
+        .with_state(state)
+}
+
+/// One operation of the API: the method and path it answers and its handler.
+struct Endpoint {
+    method: Method,
+    path: &'static str,
+    handler: MethodRouter<AppState>,
+}
+
+fn endpoint<H, T>(method: Method, path: &'static str, handler: H) -> Endpoint
+where
+    H: Handler<T, AppState>,
+    T: 'static,
+{
+    let filter = MethodFilter::try_from(method.clone()).expect("a routable method");
+    Endpoint {
+        method,
+        path,
+        handler: on(filter, handler),
+    }
+}
+
+/// Every operation described in the OpenAPI document.
+///
+/// Version 1 of the API lives under `/api/v1`: collections are plural and single
+/// items live below them, e.g. `/recipes` and `/recipes/:id`. The `/admin`
+/// routes manage the brewer, roast and tag lookup tables and require an admin
+/// bearer token.
+fn endpoints() -> Vec<Endpoint> {
+    use Method as M;
+    vec![
+        endpoint(M::GET, "/", handler),
+        endpoint(M::GET, "/healthz", health::healthz),
+        endpoint(M::GET, "/readyz", health::readyz),
+        endpoint(M::GET, "/metrics", metrics::render),
+        endpoint(M::GET, "/api/v1/roasts", roasts::get_roasts),
+        endpoint(M::GET, "/api/v1/roasts/:id", roasts::get_roast_id),
+        endpoint(M::GET, "/api/v1/brewers", brewers::get_brewers),
+        endpoint(M::GET, "/api/v1/brewers/:id", brewers::get_brewer_id),
+        endpoint(M::GET, "/api/v1/users", users::get_users),
+        endpoint(M::GET, "/api/v1/users/:id", users::get_user_id),
+        endpoint(M::GET, "/api/v1/recipes", recipes::get_recipes),
+        endpoint(M::POST, "/api/v1/recipes", recipes::create_recipe),
+        endpoint(M::GET, "/api/v1/recipes/:id", get_recipe_id),
+        endpoint(M::PUT, "/api/v1/recipes/:id", recipes::update_recipe),
+        endpoint(M::PATCH, "/api/v1/recipes/:id", recipes::patch_recipe),
+        endpoint(M::DELETE, "/api/v1/recipes/:id", recipes::delete_recipe),
+        endpoint(M::GET, "/api/v1/recipes/:id/tags", tags::get_recipe_tags),
+        endpoint(M::POST, "/api/v1/recipes/:id/tags", tags::add_recipe_tags),
+        endpoint(
+            M::DELETE,
+            "/api/v1/recipes/:id/tags/:tag_id",
+            tags::remove_recipe_tag,
+        ),
+        endpoint(
+            M::GET,
+            "/api/v1/recipes/slug/:slug",
+            recipes::get_recipe_slug,
+        ),
+        endpoint(
+            M::GET,
+            "/api/v1/recipes/title/:title",
+            recipes::get_recipe_title,
+        ),
+        endpoint(M::GET, "/api/v1/recipes/search", recipes::search_recipes),
+        endpoint(M::GET, "/api/v1/recipes/tag/:tag", recipes::get_recipes_tag),
+        endpoint(
+            M::GET,
+            "/api/v1/recipes/roaster/:roaster",
+            recipes::get_recipes_roaster,
+        ),
+        endpoint(
+            M::GET,
+            "/api/v1/recipes/machine/:machine",
+            recipes::get_recipes_machine,
+        ),
+        endpoint(M::GET, "/api/v1/tags", tags::get_tags),
+        endpoint(M::POST, "/api/v1/admin/brewers", brewers::create_brewer),
+        endpoint(M::PUT, "/api/v1/admin/brewers/:id", brewers::update_brewer),
+        endpoint(
+            M::DELETE,
+            "/api/v1/admin/brewers/:id",
+            brewers::delete_brewer,
+        ),
+        endpoint(M::POST, "/api/v1/admin/roasts", roasts::create_roast),
+        endpoint(M::PUT, "/api/v1/admin/roasts/:id", roasts::update_roast),
+        endpoint(M::DELETE, "/api/v1/admin/roasts/:id", roasts::delete_roast),
+        endpoint(M::POST, "/api/v1/admin/tags", tags::create_tag),
+        endpoint(M::PUT, "/api/v1/admin/tags/:id", tags::update_tag),
+        endpoint(M::DELETE, "/api/v1/admin/tags/:id", tags::delete_tag),
+    ]
+}
+
+/// Every `(method, path)` [`app`] routes from [`endpoints`], i.e. everything
+/// the OpenAPI document must describe. Paths use axum's `:param` syntax.
+pub fn documented_routes() -> Vec<(Method, &'static str)> {
+    endpoints()
+        .into_iter()
+        .map(|endpoint| (endpoint.method, endpoint.path))
+        .collect()
+}
 
-    // Run DB migrations
-    Migrator::up(&conn, None).await?;
+/// The unversioned `/api/...` paths served before `/api/v1`, kept so deployed
+/// clients keep working until [`LEGACY_SUNSET`].
+///
+/// Each path runs the same handler as its `/api/v1` replacement. Responses carry
+/// `Deprecation`, `Sunset` and `Link` headers pointing clients at the new API.
+/// These paths are not part of the OpenAPI document.
+fn legacy_routes() -> Router<AppState> {
+    let admin = Router::new()
+        .route("/brewers", post(brewers::create_brewer))
+        .route(
+            "/brewer/:id",
+            put(brewers::update_brewer).delete(brewers::delete_brewer),
+        )
+        .route("/roasts", post(roasts::create_roast))
+        .route(
+            "/roast/:id",
+            put(roasts::update_roast).delete(roasts::delete_roast),
+        )
+        .route("/tags", post(tags::create_tag))
+        .route("/tag/:id", put(tags::update_tag).delete(tags::delete_tag));
 
-    let app = Router::new()
-        .route("/", get(handler))
+    Router::new()
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route("/api/docs", get(openapi::docs))
         .route("/api/roasts", get(roasts::get_roasts))
         .route("/api/roast/:id", get(roasts::get_roast_id))
         .route("/api/brewers", get(brewers::get_brewers))
@@ -48,10 +310,27 @@ async fn start() -> anyhow::Result<()> {
         .route("/api/users", get(users::get_users))
         .route("/api/user/:id", get(users::get_user_id))
         .route("/api/recipes", get(recipes::get_recipes))
-        .route("/api/recipe/id/:id", get(get_recipe_id))
+        .route(
+            "/api/recipe/id/:id",
+            get(get_recipe_id)
+                .put(recipes::update_recipe)
+                .patch(recipes::patch_recipe)
+                .delete(recipes::delete_recipe),
+        )
+        .route(
+            "/api/recipe/id/:id/tags",
+            get(tags::get_recipe_tags).post(tags::add_recipe_tags),
+        )
+        .route(
+            "/api/recipe/id/:id/tags/:tag_id",
+            delete(tags::remove_recipe_tag),
+        )
         .route("/api/recipe/:slug", get(recipes::get_recipe_slug))
         .route("/api/recipe/create", post(recipes::create_recipe))
         .route("/api/recipe/title/:title", get(recipes::get_recipe_title))
+        .route("/api/recipes/search", get(recipes::search_recipes))
+        .route("/api/recipes/tag/:tag", get(recipes::get_recipes_tag))
+        .route("/api/tags", get(tags::get_tags))
         .route(
             "/api/recipes/roaster/:roaster",
             get(recipes::get_recipes_roaster),
@@ -60,44 +339,99 @@ async fn start() -> anyhow::Result<()> {
             "/api/recipes/machine/:machine",
             get(recipes::get_recipes_machine),
         )
-        // Add middleware to all routes
-        .layer(
-            ServiceBuilder::new()
-                .layer(HandleErrorLayer::new(|error: BoxError| async move {
-                    if error.is::<tower::timeout::error::Elapsed>() {
-                        Ok(StatusCode::REQUEST_TIMEOUT)
-                    } else {
-                        Err((
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            format!("Unhandled internal error: {error}"),
-                        ))
-                    }
-                }))
-                .timeout(Duration::from_secs(30))
-                .layer(TraceLayer::new_for_http())
-                .into_inner(),
-        )
-        .layer(CorsLayer::permissive())
-        .with_state(conn);
+        .nest("/api/admin", admin)
+        .route_layer(middleware::from_fn(deprecated))
+}
 
-    let app = app.fallback(handler_404);
+/// When the unversioned paths were deprecated, as an RFC 9745 `@<unix time>` date.
+const LEGACY_DEPRECATED_AT: &str = "@1792195200";
+/// When the unversioned paths may be removed, as an HTTP date (RFC 8594).
+pub const LEGACY_SUNSET: &str = "Sat, 17 Apr 2027 00:00:00 GMT";
 
-    let listener = tokio::net::TcpListener::bind(&server_url).await?;
-    tracing::debug!("listening on {}", listener.local_addr().unwrap());
-    axum::serve(listener, app).await?;
+/// Marks a response as coming from a deprecated path.
+async fn deprecated(request: Request, next: Next) -> axum::response::Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static(LEGACY_DEPRECATED_AT),
+    );
+    headers.insert(
+        HeaderName::from_static("sunset"),
+        HeaderValue::from_static(LEGACY_SUNSET),
+    );
+    headers.insert(
+        header::LINK,
+        HeaderValue::from_static("</api/v1/docs>; rel=\"deprecation\"; type=\"text/html\""),
+    );
+    response
+}
 
-    Ok(())
+/// Builds the CORS layer for the configured origins.
+fn cors_layer(config: &Config) -> CorsLayer {
+    if config.allows_any_origin() {
+        return CorsLayer::permissive();
+    }
+    let origins: Vec<HeaderValue> = config
+        .cors_origins
+        .iter()
+        .filter_map(|origin| origin.parse().ok())
+        .collect();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(Any)
+        .allow_headers(Any)
 }
 
-async fn handler() -> Response<Body> {
+/// Reports that the API is up.
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "health",
+    summary = "Service status",
+    responses(
+        (status = 200, description = "Always `{\"status\": \"ok\"}`", body = Object),
+    )
+)]
+pub(crate) async fn handler() -> Response<Body> {
     let response = json!({
         "status": "ok"
     });
     Json(response).into_response()
 }
 
-async fn handler_404() -> impl IntoResponse {
-    (StatusCode::NOT_FOUND, "nothing to see here")
+/// Resolves on Ctrl-C, or on SIGTERM on unix.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = signal::ctrl_c().await {
+            tracing::error!("cannot listen for Ctrl-C: {err}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("cannot listen for SIGTERM: {err}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+}
+
+async fn handler_404() -> ApiError {
+    ApiError::NotFound("nothing to see here".to_string())
 }
 
 pub fn main() {
@@ -0,0 +1,477 @@
+use axum::{extract::State, http::StatusCode, response::Json};
+use entity::recipe as Recipe;
+use entity::tag as Tag;
+use entity::tag_recipe as TagRecipe;
+
+use super::auth::{AdminUser, AuthUser};
+use super::error::{ApiError, ErrorBody};
+use super::extract::{FormOrJson, Path, Query};
+use super::pagination::{paginate, Paginated, Pagination};
+use super::recipes::{find_owned_recipe, recipe_not_found};
+use sea_orm::{
+    entity::*,
+    query::*,
+    sea_query::{Expr, Func, OnConflict},
+    ConnectionTrait, DatabaseConnection, DbErr, FromQueryResult, SqlErr,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A tag along with the number of recipes it is attached to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromQueryResult, ToSchema)]
+pub struct TagUsage {
+    pub id: i32,
+    pub title: String,
+    pub recipes: i64,
+}
+
+/// Body accepted when attaching tags to a recipe.
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct TagList {
+    /// Comma separated tag titles. Unknown tags are created.
+    pub tags: String,
+}
+
+/// Retrieves every tag along with how many recipes use it.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `Query(page)` - The pagination parameters, see [`Pagination`].
+///
+/// # Returns
+///
+/// * `Ok(Json<Paginated<TagUsage>>)` - A page of tags with their usage counts.
+/// * `Err(ApiError)` - If the database query fails.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tags",
+    tag = "tags",
+    summary = "List tags",
+    params(Pagination),
+    responses(
+        (status = 200, description = "One page of tags with their usage counts", body = Paginated<TagUsage>),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn get_tags(
+    State(conn): State<DatabaseConnection>,
+    Query(page): Query<Pagination>,
+) -> Result<Json<Paginated<TagUsage>>, ApiError> {
+    let query = Tag::Entity::find()
+        .select_only()
+        .columns([Tag::Column::Id, Tag::Column::Title])
+        .column_as(TagRecipe::Column::Id.count(), "recipes")
+        .left_join(TagRecipe::Entity)
+        .group_by(Tag::Column::Id)
+        .group_by(Tag::Column::Title);
+    let tags = paginate(&conn, query, Tag::Column::Id, &page, |tag: &TagUsage| {
+        tag.id
+    })
+    .await?;
+    Ok(Json(tags))
+}
+
+/// Retrieves the tags attached to a recipe.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `Path(id)` - The ID of the recipe.
+///
+/// # Returns
+///
+/// * `Ok(Json<Vec<Tag::Model>>)` - The recipe's tags ordered by title.
+/// * `Err(ApiError)` - `StatusCode::NOT_FOUND` if the recipe does not exist, or an internal
+///   error if the database query fails.
+#[utoipa::path(
+    get,
+    path = "/api/v1/recipes/{id}/tags",
+    tag = "tags",
+    summary = "List a recipe's tags",
+    params(("id" = i32, Path, description = "Id of the recipe")),
+    responses(
+        (status = 200, description = "The tags on the recipe", body = Vec<Tag::Model>),
+        (status = 404, description = "No recipe with this id", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn get_recipe_tags(
+    State(conn): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<Tag::Model>>, ApiError> {
+    Recipe::Entity::find_by_id(id)
+        .one(&conn)
+        .await?
+        .ok_or_else(recipe_not_found)?;
+    let tags = recipe_tags(&conn, id).await?;
+    Ok(Json(tags))
+}
+
+/// Attaches tags to a recipe, creating any tag that does not exist yet.
+///
+/// Tags are matched case-insensitively, so attaching `Fruity` reuses an existing
+/// `fruity` tag. Tags already on the recipe are left alone.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `AuthUser(user)` - The user the bearer token belongs to. Only the owner of the
+///   recipe, or a moderator, may tag it.
+/// * `Path(id)` - The ID of the recipe to tag.
+/// * `FormOrJson(form)` - The tags to attach, as a form or as JSON.
+///
+/// # Returns
+///
+/// * `Ok(Json<Vec<Tag::Model>>)` - Every tag now attached to the recipe.
+/// * `Err(ApiError)` - If the tags could not be attached.
+///
+/// # Errors
+///
+/// * `StatusCode::NOT_FOUND` if no recipe has the given ID.
+/// * `StatusCode::FORBIDDEN` if the user may not modify the recipe.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if a database query fails.
+#[utoipa::path(
+    post,
+    path = "/api/v1/recipes/{id}/tags",
+    tag = "tags",
+    summary = "Tag a recipe",
+    description = "Unknown tags are created. Only the owner, moderators and admins may tag a recipe.",
+    params(("id" = i32, Path, description = "Id of the recipe")),
+    request_body(content(
+        (TagList = "application/json"),
+        (TagList = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Every tag now on the recipe", body = Vec<Tag::Model>),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not the owner or a moderator", body = ErrorBody),
+        (status = 404, description = "No recipe with this id", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn add_recipe_tags(
+    State(conn): State<DatabaseConnection>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i32>,
+    FormOrJson(form): FormOrJson<TagList>,
+) -> Result<Json<Vec<Tag::Model>>, ApiError> {
+    let recipe = find_owned_recipe(&conn, &user, id).await?;
+
+    let txn = conn.begin().await?;
+    for title in form.tags.split(',').map(str::trim) {
+        if title.is_empty() {
+            continue;
+        }
+        let tag = find_or_create_tag(&txn, title).await?;
+        let link = TagRecipe::ActiveModel {
+            id: Default::default(),
+            tag_id: ActiveValue::set(tag.id),
+            recipe_id: ActiveValue::set(recipe.id),
+        };
+        TagRecipe::Entity::insert(link)
+            .on_conflict(
+                OnConflict::columns([TagRecipe::Column::TagId, TagRecipe::Column::RecipeId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+
+    let tags = recipe_tags(&conn, recipe.id).await?;
+    Ok(Json(tags))
+}
+
+/// Detaches a tag from a recipe. The tag itself is kept.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `AuthUser(user)` - The user the bearer token belongs to. Only the owner of the
+///   recipe, or a moderator, may untag it.
+/// * `Path((id, tag_id))` - The IDs of the recipe and of the tag to detach.
+///
+/// # Errors
+///
+/// * `StatusCode::NOT_FOUND` if no recipe has the given ID or the tag is not attached to it.
+/// * `StatusCode::FORBIDDEN` if the user may not modify the recipe.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if a database query fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/recipes/{id}/tags/{tag_id}",
+    tag = "tags",
+    summary = "Remove a tag from a recipe",
+    params(("id" = i32, Path, description = "Id of the recipe"), ("tag_id" = i32, Path, description = "Id of the tag")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Removed"),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not the owner or a moderator", body = ErrorBody),
+        (status = 404, description = "No such recipe, or the tag is not on it", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn remove_recipe_tag(
+    State(conn): State<DatabaseConnection>,
+    AuthUser(user): AuthUser,
+    Path((id, tag_id)): Path<(i32, i32)>,
+) -> Result<StatusCode, ApiError> {
+    let recipe = find_owned_recipe(&conn, &user, id).await?;
+    let result = TagRecipe::Entity::delete_many()
+        .filter(TagRecipe::Column::RecipeId.eq(recipe.id))
+        .filter(TagRecipe::Column::TagId.eq(tag_id))
+        .exec(&conn)
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(ApiError::NotFound(
+            "tag is not attached to this recipe".to_string(),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates a new tag. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `FormOrJson(form)` - The tag to create, as a form or as JSON.
+///
+/// # Returns
+///
+/// * `Ok(Json(tag))` - The newly created tag.
+/// * `Err(ApiError)` - If the tag could not be created, or
+///   `StatusCode::CONFLICT` if a tag with the same title already exists.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/tags",
+    tag = "tags",
+    summary = "Create a tag",
+    description = "Only available to admins. Only `title` is read from the body.",
+    request_body(content(
+        (Tag::Model = "application/json"),
+        (Tag::Model = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The created tag", body = Tag::Model),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 409, description = "The title is taken", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn create_tag(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    FormOrJson(form): FormOrJson<Tag::Model>,
+) -> Result<Json<Tag::Model>, ApiError> {
+    ensure_title_free(&conn, &form.title, None).await?;
+    let tag = Tag::ActiveModel {
+        id: Default::default(),
+        title: ActiveValue::set(form.title.clone()),
+    };
+    let result = tag
+        .insert(&conn)
+        .await
+        .map_err(|err| title_taken(err, &form.title))?;
+    Ok(Json(result))
+}
+
+/// Renames an existing tag. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `Path(id)` - The ID of the tag to rename.
+/// * `FormOrJson(form)` - The new tag title, as a form or as JSON.
+///
+/// # Errors
+///
+/// * `StatusCode::NOT_FOUND` if no tag has the given ID.
+/// * `StatusCode::CONFLICT` if another tag already has the new title.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if the database update fails.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/tags/{id}",
+    tag = "tags",
+    summary = "Rename a tag",
+    description = "Only available to admins. Only `title` is read from the body.",
+    params(("id" = i32, Path, description = "Id of the tag")),
+    request_body(content(
+        (Tag::Model = "application/json"),
+        (Tag::Model = "application/x-www-form-urlencoded"),
+    )),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The renamed tag", body = Tag::Model),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "No tag with this id", body = ErrorBody),
+        (status = 409, description = "The title is taken", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn update_tag(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    Path(id): Path<i32>,
+    FormOrJson(form): FormOrJson<Tag::Model>,
+) -> Result<Json<Tag::Model>, ApiError> {
+    let existing = find_tag(&conn, id).await?;
+    ensure_title_free(&conn, &form.title, Some(existing.id)).await?;
+    let mut tag: Tag::ActiveModel = existing.into();
+    tag.title = ActiveValue::set(form.title.clone());
+    let result = tag
+        .update(&conn)
+        .await
+        .map_err(|err| title_taken(err, &form.title))?;
+    Ok(Json(result))
+}
+
+/// Deletes a tag. Only available to admins.
+///
+/// # Arguments
+///
+/// * `State(conn)` - The database connection state.
+/// * `_: AdminUser` - Rejects the request unless the bearer token belongs to an admin.
+/// * `Path(id)` - The ID of the tag to delete.
+///
+/// # Errors
+///
+/// * `StatusCode::NOT_FOUND` if no tag has the given ID.
+/// * `StatusCode::CONFLICT` if the tag is still attached to any recipe.
+/// * `StatusCode::INTERNAL_SERVER_ERROR` if the database delete fails.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/tags/{id}",
+    tag = "tags",
+    summary = "Delete a tag",
+    description = "Only available to admins. A tag can only be deleted once no recipe uses it.",
+    params(("id" = i32, Path, description = "Id of the tag")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 401, description = "Missing or invalid bearer token", body = ErrorBody),
+        (status = 403, description = "Not an admin", body = ErrorBody),
+        (status = 404, description = "No tag with this id", body = ErrorBody),
+        (status = 409, description = "Recipes still use the tag", body = ErrorBody),
+        (status = 429, description = "Rate limit exceeded", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
+pub async fn delete_tag(
+    State(conn): State<DatabaseConnection>,
+    _: AdminUser,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let tag = find_tag(&conn, id).await?;
+    let in_use = TagRecipe::Entity::find()
+        .filter(TagRecipe::Column::TagId.eq(tag.id))
+        .count(&conn)
+        .await?;
+    if in_use > 0 {
+        return Err(ApiError::Conflict(format!(
+            "tag is attached to {in_use} recipe(s)"
+        )));
+    }
+    tag.delete(&conn).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_tag(conn: &DatabaseConnection, id: i32) -> Result<Tag::Model, ApiError> {
+    Tag::Entity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("tag not found".to_string()))
+}
+
+/// Finds a tag by title ignoring case.
+///
+/// Both sides are lowercased by the database, the same way as the unique index
+/// on `lower(title)`.
+async fn find_tag_by_title<C: ConnectionTrait>(
+    conn: &C,
+    title: &str,
+) -> Result<Option<Tag::Model>, DbErr> {
+    Tag::Entity::find()
+        .filter(
+            Expr::expr(Func::lower(Expr::col((Tag::Entity, Tag::Column::Title))))
+                .eq(Func::lower(Expr::val(title))),
+        )
+        .one(conn)
+        .await
+}
+
+/// Returns the tag matching `title` ignoring case, creating it if needed.
+///
+/// A tag created by a concurrent request after the first lookup makes the insert
+/// a no-op through the unique index on `lower(title)`, and is read back instead.
+async fn find_or_create_tag<C: ConnectionTrait>(
+    conn: &C,
+    title: &str,
+) -> Result<Tag::Model, DbErr> {
+    if let Some(tag) = find_tag_by_title(conn, title).await? {
+        return Ok(tag);
+    }
+    let tag = Tag::ActiveModel {
+        id: Default::default(),
+        title: ActiveValue::set(title.to_string()),
+    };
+    Tag::Entity::insert(tag)
+        .on_conflict(OnConflict::new().do_nothing().to_owned())
+        .exec_without_returning(conn)
+        .await?;
+    find_tag_by_title(conn, title)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("tag {title}")))
+}
+
+/// Reports a unique violation on the tag title as a 409, for a title taken
+/// between [`ensure_title_free`] and the write.
+fn title_taken(err: DbErr, title: &str) -> ApiError {
+    match err.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => {
+            ApiError::Conflict(format!("tag {title} already exists"))
+        }
+        _ => err.into(),
+    }
+}
+
+/// Rejects a title that another tag already uses, ignoring case.
+///
+/// `except` is the id of the tag being renamed, which may keep its own title or
+/// change only its case.
+async fn ensure_title_free(
+    conn: &DatabaseConnection,
+    title: &str,
+    except: Option<i32>,
+) -> Result<(), ApiError> {
+    let existing = find_tag_by_title(conn, title).await?;
+    match existing {
+        Some(tag) if Some(tag.id) != except => Err(ApiError::Conflict(format!(
+            "tag {} already exists",
+            tag.title
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Loads the tags attached to a recipe, ordered by title.
+async fn recipe_tags(conn: &DatabaseConnection, recipe_id: i32) -> Result<Vec<Tag::Model>, DbErr> {
+    Tag::Entity::find()
+        .inner_join(TagRecipe::Entity)
+        .filter(TagRecipe::Column::RecipeId.eq(recipe_id))
+        .order_by_asc(Tag::Column::Title)
+        .all(conn)
+        .await
+}
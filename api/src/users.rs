@@ -1,44 +1,60 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-};
+use axum::{extract::State, response::Json};
 
-use super::internal_error;
+use super::error::{ApiError, ErrorBody};
+use super::extract::{Path, Query};
+use super::pagination::{paginate, Paginated, Pagination};
 use entity::user as User;
 use sea_orm::{entity::*, DatabaseConnection, FromQueryResult, QuerySelect};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Asynchronously retrieves a list of users from the database.
 ///
 /// This function uses a given `DatabaseConnection` to fetch the list of users
 /// with only the `Id` and `Username` columns. The result is then converted
-/// into a JSON vector of `PartialUser` structures and returned.
+/// into a page of `PartialUser` structures and returned as JSON.
 ///
 /// # Arguments
 ///
 /// - `State(conn)`: A `State` wrapper containing the database connection.
+/// - `Query(page)`: The pagination parameters, see [`Pagination`].
 ///
 /// # Returns
 ///
-/// - `Result<Json<Vec<PartialUser>>, (StatusCode, String)>`:
-///   - `Ok(Json(Vec<PartialUser>))`: JSON vector of users if the retrieval is successful.
-///   - `Err((StatusCode, String))`: A tuple containing an HTTP status code and an error
-///     message if the retrieval fails.
+/// - `Result<Json<Paginated<PartialUser>>, ApiError>`:
+///   - `Ok(Json(Paginated<PartialUser>))`: A page of users if the retrieval is successful.
+///   - `Err(ApiError)`: An error rendered as a JSON error response if the
+///     retrieval fails.
 ///
 /// # Errors
 ///
 /// This function returns an error if there is an issue with querying the database.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    tag = "users",
+    summary = "List users",
+    params(Pagination),
+    responses(
+        (status = 200, description = "One page of users", body = Paginated<PartialUser>),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_users(
     State(conn): State<DatabaseConnection>,
-) -> Result<Json<Vec<PartialUser>>, (StatusCode, String)> {
-    let users = User::Entity::find()
+    Query(page): Query<Pagination>,
+) -> Result<Json<Paginated<PartialUser>>, ApiError> {
+    let query = User::Entity::find()
         .select_only()
-        .columns([User::Column::Id, User::Column::Username])
-        .into_model::<PartialUser>()
-        .all(&conn)
-        .await
-        .map_err(internal_error)?;
+        .columns([User::Column::Id, User::Column::Username]);
+    let users = paginate(
+        &conn,
+        query,
+        User::Column::Id,
+        &page,
+        |user: &PartialUser| user.id,
+    )
+    .await?;
     Ok(Json(users))
 }
 
@@ -54,31 +70,43 @@ pub async fn get_users(
 ///
 /// # Returns
 ///
-/// This function returns a `Result<Json<Option<PartialUser>>, (StatusCode, String)>`.
-/// - On success: `Ok(Json(Some(partial_user)))` containing the user's partial details wrapped in `Json`.
-/// - On error: `Err((StatusCode, String))` with an appropriate status code and error message.
+/// This function returns a `Result<Json<PartialUser>, ApiError>`.
+/// - On success: `Ok(Json(partial_user))` containing the user's partial details wrapped in `Json`.
+/// - On error: `Err(ApiError)` with an appropriate status code and error message.
 ///
 /// # Errors
 ///
-/// This function maps any internal errors encountered during the database query to a custom
-/// error using the `internal_error` mapping function.
+/// Returns `ApiError::NotFound` if no user has the given ID. Any internal errors encountered
+/// during the database query are mapped using `ApiError`'s `From<DbErr>` conversion.
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}",
+    tag = "users",
+    summary = "Get a user",
+    params(("id" = i32, Path, description = "Id of the user")),
+    responses(
+        (status = 200, description = "The user", body = PartialUser),
+        (status = 404, description = "No user with this id", body = ErrorBody),
+        (status = 500, description = "Database error", body = ErrorBody),
+    )
+)]
 pub async fn get_user_id(
     State(conn): State<DatabaseConnection>,
     Path(id): Path<i32>,
-) -> Result<Json<Option<PartialUser>>, (StatusCode, String)> {
+) -> Result<Json<PartialUser>, ApiError> {
     let user = User::Entity::find_by_id(id)
         .select_only()
         .columns([User::Column::Id, User::Column::Username])
         .into_model::<PartialUser>()
         .one(&conn)
-        .await
-        .map_err(internal_error)?;
+        .await?
+        .ok_or_else(|| ApiError::NotFound("user not found".to_string()))?;
     Ok(Json(user))
 }
 
 /// Restricts what is returned from the User model to prevent
 /// sensitive info from appearing in the results.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromQueryResult)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromQueryResult, ToSchema)]
 pub struct PartialUser {
     pub id: i32,
     pub username: String,
@@ -0,0 +1,160 @@
+use entity::brewer as Brewer;
+use entity::roast as Roast;
+use sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait};
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use url::Url;
+
+use super::error::{ApiError, FieldError};
+use super::recipes::CreateRecipe;
+use super::xbloom::share_id;
+
+/// Longest title a recipe may have.
+pub const MAX_TITLE_LEN: usize = 100;
+/// Longest roaster name a recipe may have.
+pub const MAX_ROASTER_LEN: usize = 100;
+/// Longest creator name a recipe may have.
+pub const MAX_CREATOR_LEN: usize = 100;
+/// Serving temperatures the xBloom app offers.
+pub const TEMPS: [&str; 2] = ["hot", "iced"];
+/// Coffee doses, in grams, a recipe may call for.
+pub const DOSE_GRAMS: RangeInclusive<f64> = 1.0..=50.0;
+/// Settings of the xBloom's built-in grinder.
+pub const GRIND_SIZES: RangeInclusive<i32> = 1..=80;
+/// Water to coffee ratios a recipe may call for.
+pub const RATIOS: RangeInclusive<f64> = 1.0..=30.0;
+/// Most pours a recipe may have.
+pub const MAX_STEPS: usize = 20;
+/// Water volumes, in millilitres, a single pour may use.
+pub const STEP_VOLUMES_ML: RangeInclusive<i32> = 1..=500;
+/// Water temperatures, in degrees Celsius, a pour may use.
+pub const STEP_TEMPERATURES_C: RangeInclusive<i32> = 20..=100;
+/// Flow rates, in millilitres per second, a pour may use.
+pub const STEP_FLOW_RATES: RangeInclusive<f64> = 0.5..=10.0;
+/// Pour patterns the xBloom app offers.
+pub const POUR_PATTERNS: [&str; 3] = ["center", "circular", "spiral"];
+/// Pauses, in seconds, allowed after a pour.
+pub const STEP_PAUSES_SECS: RangeInclusive<i32> = 0..=600;
+
+/// Collects field errors so a client sees every problem with a submission at once.
+#[derive(Debug, Default)]
+struct Errors(Vec<FieldError>);
+
+impl Errors {
+    fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn length(&mut self, field: &str, value: &str, max: usize) {
+        let len = value.trim().chars().count();
+        if len == 0 {
+            self.add(field, "must not be empty");
+        } else if len > max {
+            self.add(field, format!("must be at most {max} characters"));
+        }
+    }
+
+    fn range<T: PartialOrd + Display>(&mut self, field: &str, value: T, range: &RangeInclusive<T>) {
+        if !range.contains(&value) {
+            self.add(
+                field,
+                format!("must be between {} and {}", range.start(), range.end()),
+            );
+        }
+    }
+
+    fn into_result(self) -> Result<(), ApiError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Invalid(self.0))
+        }
+    }
+}
+
+/// Checks a recipe submission before it is written.
+///
+/// `creator` is the name that will be stored as the recipe's creator.
+///
+/// # Errors
+///
+/// * `ApiError::Invalid` listing every field that failed validation, including
+///   `brewer_id` and `roast_id` values that do not reference an existing row.
+/// * `ApiError::Internal` if looking up a foreign key fails.
+pub async fn validate_recipe(
+    conn: &DatabaseConnection,
+    input: &CreateRecipe,
+    creator: &str,
+) -> Result<(), ApiError> {
+    let mut errors = Errors::default();
+
+    errors.length("title", &input.title, MAX_TITLE_LEN);
+    errors.length("roaster", &input.roaster, MAX_ROASTER_LEN);
+    errors.length("creator", creator, MAX_CREATOR_LEN);
+
+    if !TEMPS.contains(&input.temp.as_str()) {
+        errors.add("temp", format!("must be one of {}", TEMPS.join(", ")));
+    }
+    if share_id(&input.link).is_none() {
+        errors.add("link", "must be an xBloom recipe share link");
+    }
+    if !is_web_url(&input.shop_link) {
+        errors.add("shop_link", "must be an http or https URL");
+    }
+
+    if let Some(dose) = input.dose_grams {
+        errors.range("dose_grams", dose, &DOSE_GRAMS);
+    }
+    if let Some(grind) = input.grind_size {
+        errors.range("grind_size", grind, &GRIND_SIZES);
+    }
+    if let Some(ratio) = input.ratio {
+        errors.range("ratio", ratio, &RATIOS);
+    }
+    let steps = input.steps.as_deref().unwrap_or_default();
+    if steps.len() > MAX_STEPS {
+        errors.add("steps", format!("must have at most {MAX_STEPS} pours"));
+    }
+    for (i, step) in steps.iter().enumerate() {
+        let field = |name: &str| format!("steps[{i}].{name}");
+        errors.range(&field("volume_ml"), step.volume_ml, &STEP_VOLUMES_ML);
+        errors.range(
+            &field("temperature_c"),
+            step.temperature_c,
+            &STEP_TEMPERATURES_C,
+        );
+        errors.range(&field("flow_rate"), step.flow_rate, &STEP_FLOW_RATES);
+        if !POUR_PATTERNS.contains(&step.pattern.as_str()) {
+            errors.add(
+                &field("pattern"),
+                format!("must be one of {}", POUR_PATTERNS.join(", ")),
+            );
+        }
+        errors.range(&field("pause_secs"), step.pause_secs, &STEP_PAUSES_SECS);
+    }
+
+    let brewers = Brewer::Entity::find_by_id(input.brewer_id)
+        .count(conn)
+        .await?;
+    if brewers == 0 {
+        errors.add("brewer_id", "does not match a known brewer");
+    }
+    let roasts = Roast::Entity::find_by_id(input.roast_id)
+        .count(conn)
+        .await?;
+    if roasts == 0 {
+        errors.add("roast_id", "does not match a known roast");
+    }
+
+    errors.into_result()
+}
+
+/// Returns true for an absolute `http` or `https` URL with a host.
+fn is_web_url(link: &str) -> bool {
+    Url::parse(link)
+        .map(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+        .unwrap_or(false)
+}
@@ -0,0 +1,38 @@
+use url::Url;
+
+/// Hosts that serve xBloom recipe share pages.
+pub const SHARE_HOSTS: [&str; 1] = ["share-h5.xbloom.com"];
+
+/// Extracts the canonical share id from an xBloom recipe share link.
+///
+/// The xBloom app shares recipes as links like
+/// `https://share-h5.xbloom.com/?id=8yAUAWJktyHNIpo3vjZ6pA==`, where the `id`
+/// query parameter alone identifies the recipe. Links that only differ in host
+/// case, a trailing slash, extra query parameters, a fragment or how the id is
+/// percent-encoded therefore share the same id.
+///
+/// Ids are base64, which never contains spaces, so a space in the decoded id is
+/// a `+` that was sent unencoded and is turned back into one.
+///
+/// # Arguments
+///
+/// * `link` - The link as submitted.
+///
+/// # Returns
+///
+/// * `Some(id)` for an `https` link to an xBloom share page with a non-empty `id`.
+/// * `None` for anything else.
+pub fn share_id(link: &str) -> Option<String> {
+    let url = Url::parse(link.trim()).ok()?;
+    if url.scheme() != "https"
+        || !url
+            .host_str()
+            .is_some_and(|host| SHARE_HOSTS.contains(&host))
+    {
+        return None;
+    }
+    url.query_pairs()
+        .filter(|(key, _)| key == "id")
+        .map(|(_, value)| value.trim().replace(' ', "+"))
+        .find(|id| !id.is_empty())
+}
@@ -0,0 +1,222 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{TestApp, SEEDED_RECIPE_ID};
+use entity::sea_orm_active_enums::Role;
+use serde_json::json;
+
+#[tokio::test]
+async fn admin_routes_require_an_admin() {
+    let app = TestApp::spawn().await;
+    let (_, member) = app.user("member", Role::Member).await;
+    let (_, moderator) = app.user("moderator", Role::Moderator).await;
+
+    let (status, _) = app
+        .form(Method::POST, "/api/v1/admin/brewers", None, "type=V60")
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    for token in [&member, &moderator] {
+        let (status, body) = app
+            .form(
+                Method::POST,
+                "/api/v1/admin/brewers",
+                Some(token),
+                "type=V60",
+            )
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["message"], "admin access required");
+    }
+}
+
+#[tokio::test]
+async fn manage_brewers() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token();
+
+    let (status, body) = app
+        .form(
+            Method::POST,
+            "/api/v1/admin/brewers",
+            Some(&admin),
+            "type=V60",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 6, "type": "V60" }));
+
+    let (status, body) = app
+        .json(
+            Method::POST,
+            "/api/v1/admin/brewers",
+            Some(&admin),
+            &json!({ "type": "Origami" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 7, "type": "Origami" }));
+    let (status, body) = app
+        .json(
+            Method::POST,
+            "/api/v1/admin/brewers",
+            Some(&admin),
+            &json!({ "kind": "Origami" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "unprocessable_entity");
+
+    let (status, body) = app
+        .form(
+            Method::PUT,
+            "/api/v1/admin/brewers/6",
+            Some(&admin),
+            "type=Kalita",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["type"], "Kalita");
+
+    let (status, _) = app.delete("/api/v1/admin/brewers/6", Some(&admin)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.get("/api/v1/brewers/6").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The seeded recipe uses brewer 1.
+    let (status, body) = app.delete("/api/v1/admin/brewers/1", Some(&admin)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "brewer is used by 1 recipe(s)");
+
+    let (status, _) = app
+        .form(
+            Method::PUT,
+            "/api/v1/admin/brewers/999",
+            Some(&admin),
+            "type=x",
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn manage_roasts() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token();
+
+    let (status, body) = app
+        .form(
+            Method::POST,
+            "/api/v1/admin/roasts",
+            Some(&admin),
+            "level=Nordic",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 7, "level": "Nordic" }));
+
+    let (status, body) = app
+        .form(
+            Method::PUT,
+            "/api/v1/admin/roasts/7",
+            Some(&admin),
+            "level=Ultra-light",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["level"], "Ultra-light");
+
+    let (status, _) = app.delete("/api/v1/admin/roasts/7", Some(&admin)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = app.delete("/api/v1/admin/roasts/1", Some(&admin)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = app.delete("/api/v1/admin/roasts/999", Some(&admin)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn manage_tags() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token();
+
+    let (status, body) = app
+        .form(
+            Method::POST,
+            "/api/v1/admin/tags",
+            Some(&admin),
+            "title=Floral",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 1, "title": "Floral" }));
+
+    let (status, _) = app
+        .form(
+            Method::POST,
+            "/api/v1/admin/tags",
+            Some(&admin),
+            "title=floral",
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = app
+        .form(
+            Method::PUT,
+            "/api/v1/admin/tags/1",
+            Some(&admin),
+            "title=Jasmine",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["title"], "Jasmine");
+
+    // Renaming a tag to itself, or changing only its case, is not a conflict.
+    for title in ["Jasmine", "jasmine"] {
+        let (status, body) = app
+            .form(
+                Method::PUT,
+                "/api/v1/admin/tags/1",
+                Some(&admin),
+                &format!("title={title}"),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["title"], title);
+    }
+    app.form(
+        Method::POST,
+        "/api/v1/admin/tags",
+        Some(&admin),
+        "title=Bergamot",
+    )
+    .await;
+    let (status, _) = app
+        .form(
+            Method::PUT,
+            "/api/v1/admin/tags/1",
+            Some(&admin),
+            "title=BERGAMOT",
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    app.form(
+        Method::POST,
+        &format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags"),
+        Some(&admin),
+        "tags=jasmine",
+    )
+    .await;
+    let (status, _) = app.delete("/api/v1/admin/tags/1", Some(&admin)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    app.delete(
+        &format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags/1"),
+        Some(&admin),
+    )
+    .await;
+    let (status, _) = app.delete("/api/v1/admin/tags/1", Some(&admin)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
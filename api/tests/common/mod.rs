@@ -0,0 +1,303 @@
+//! Shared harness for the integration tests.
+//!
+//! Every test gets its own freshly migrated database. When `DATABASE_URL` points
+//! at Postgres the database is created next to it and dropped again when the
+//! [`TestApp`] goes away; otherwise a private in-memory SQLite database is used.
+#![allow(dead_code)]
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, Response, StatusCode},
+    Router,
+};
+use bloom_api::auth::Claims;
+use bloom_api::config::Config;
+use bloom_api::server::{app, AppState};
+use chrono::Utc;
+use entity::sea_orm_active_enums::Role;
+use entity::user as User;
+use http_body_util::BodyExt;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ConnectOptions, ConnectionTrait, Database, DatabaseConnection,
+};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tower::ServiceExt;
+use url::Url;
+
+/// Secret the test app verifies bearer tokens with.
+pub const JWT_SECRET: &str = "integration-test-secret";
+/// Id of the admin user created by the migrations.
+pub const SEEDED_ADMIN_ID: i32 = 1;
+/// Id of "The Future", the recipe created by the migrations.
+pub const SEEDED_RECIPE_ID: i32 = 1;
+
+static DB_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// The bloomdb router backed by a throwaway database.
+pub struct TestApp {
+    router: Router,
+    pub conn: DatabaseConnection,
+    _db: TestDb,
+}
+
+impl TestApp {
+    /// Creates and migrates a new database and builds the router on top of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics when there is no Postgres `DATABASE_URL` and the `sqlite` feature
+    /// is disabled, so a build without a test database fails instead of passing.
+    pub async fn spawn() -> TestApp {
+        TestApp::spawn_with(|_| {}).await
+    }
+
+    /// Like [`TestApp::spawn`], but lets `configure` change the settings first.
+    pub async fn spawn_with(configure: impl FnOnce(&mut Config)) -> TestApp {
+        let db = match std::env::var("DATABASE_URL") {
+            Ok(url) if url.starts_with("postgres") => TestDb::postgres(&url).await,
+            _ if cfg!(feature = "sqlite") => TestDb::sqlite(),
+            _ => panic!(
+                "the integration tests need a database: set DATABASE_URL to a Postgres \
+                 server or build with the `sqlite` feature"
+            ),
+        };
+
+        // An in-memory SQLite database only exists on the connection that made it.
+        let pool_size = if db.cleanup.is_some() { 5 } else { 1 };
+        let mut options = ConnectOptions::new(db.url.clone());
+        options.max_connections(pool_size).sqlx_logging(false);
+        let conn = Database::connect(options)
+            .await
+            .expect("connect to test database");
+        Migrator::up(&conn, None).await.expect("run migrations");
+
+        let mut config = Config {
+            database_url: db.url.clone(),
+            jwt_secret: Some(JWT_SECRET.to_string()),
+            ..Config::default()
+        };
+        configure(&mut config);
+        let router = app(AppState::new(conn.clone(), config));
+        TestApp {
+            router,
+            conn,
+            _db: db,
+        }
+    }
+
+    /// Sends `request` through the router and returns the status and the JSON
+    /// body, or `Value::Null` for an empty body.
+    pub async fn send(&self, request: Request<Body>) -> (StatusCode, Value) {
+        let response = self.response(request).await;
+        let status = response.status();
+        let bytes = response
+            .into_body()
+            .collect()
+            .await
+            .expect("read body")
+            .to_bytes();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+        (status, body)
+    }
+
+    /// Sends `request` through the router and returns the raw response.
+    pub async fn response(&self, request: Request<Body>) -> Response<Body> {
+        self.router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("router is infallible")
+    }
+
+    pub async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        self.send(request(Method::GET, uri, None).body(Body::empty()).unwrap())
+            .await
+    }
+
+    pub async fn delete(&self, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+        self.send(
+            request(Method::DELETE, uri, token)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+    }
+
+    pub async fn json(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: &Value,
+    ) -> (StatusCode, Value) {
+        let request = request(method, uri, token)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        self.send(request).await
+    }
+
+    pub async fn form(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: &str,
+    ) -> (StatusCode, Value) {
+        let request = request(method, uri, token)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        self.send(request).await
+    }
+
+    /// Inserts a user with `role` and returns it with a valid bearer token.
+    pub async fn user(&self, username: &str, role: Role) -> (User::Model, String) {
+        let user = User::ActiveModel {
+            email: ActiveValue::set(format!("{username}@example.com")),
+            username: ActiveValue::set(username.to_string()),
+            created_at: ActiveValue::set(Utc::now().naive_utc()),
+            role: ActiveValue::set(role),
+            ..Default::default()
+        }
+        .insert(&self.conn)
+        .await
+        .expect("insert user");
+        let token = token_for(user.id);
+        (user, token)
+    }
+
+    /// A bearer token for the seeded admin.
+    pub fn admin_token(&self) -> String {
+        token_for(SEEDED_ADMIN_ID)
+    }
+}
+
+/// Signs a token for user `id` that expires in an hour.
+pub fn token_for(id: i32) -> String {
+    let claims = Claims {
+        sub: id.to_string(),
+        exp: (Utc::now().timestamp() + 3600) as usize,
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .expect("sign token")
+}
+
+/// A valid JSON body for the create and update recipe endpoints.
+///
+/// The xBloom share id is derived from `title`, so bodies with different titles
+/// never count as duplicates of each other.
+pub fn recipe_body(title: &str) -> Value {
+    let share_id: String = title.chars().filter(char::is_ascii_alphanumeric).collect();
+    json!({
+        "title": title,
+        "roaster": "Onyx",
+        "temp": "hot",
+        "link": format!("https://share-h5.xbloom.com/?id={share_id}"),
+        "shop_link": "https://onyxcoffeelab.com/products/geometry",
+        "machine": "Studio",
+        "brewer_id": 1,
+        "roast_id": 1,
+    })
+}
+
+/// The `items` array of a paginated response.
+pub fn items(body: &Value) -> &Vec<Value> {
+    body["items"].as_array().expect("paginated response")
+}
+
+/// The `title` of every item in a paginated response.
+pub fn titles(body: &Value) -> Vec<&str> {
+    items(body)
+        .iter()
+        .map(|item| item["title"].as_str().unwrap())
+        .collect()
+}
+
+fn request(method: Method, uri: &str, token: Option<&str>) -> axum::http::request::Builder {
+    let builder = Request::builder().method(method).uri(uri);
+    match token {
+        Some(token) => builder.header(header::AUTHORIZATION, format!("Bearer {token}")),
+        None => builder,
+    }
+}
+
+/// A database created for a single test.
+struct TestDb {
+    url: String,
+    /// For Postgres, the server URL and the name of the database to drop
+    /// when this goes out of scope.
+    cleanup: Option<(String, String)>,
+}
+
+impl TestDb {
+    fn sqlite() -> TestDb {
+        TestDb {
+            url: "sqlite::memory:".to_string(),
+            cleanup: None,
+        }
+    }
+
+    async fn postgres(base_url: &str) -> TestDb {
+        let name = format!(
+            "bloom_test_{}_{}",
+            std::process::id(),
+            DB_COUNTER.fetch_add(1, Ordering::SeqCst)
+        );
+        let admin = Database::connect(base_url)
+            .await
+            .expect("connect to DATABASE_URL");
+        admin
+            .execute_unprepared(&format!("DROP DATABASE IF EXISTS \"{name}\""))
+            .await
+            .expect("drop stale test database");
+        admin
+            .execute_unprepared(&format!("CREATE DATABASE \"{name}\""))
+            .await
+            .expect("create test database");
+        admin.close().await.ok();
+
+        let mut url = Url::parse(base_url).expect("DATABASE_URL is a URL");
+        url.set_path(&name);
+        TestDb {
+            url: url.to_string(),
+            cleanup: Some((base_url.to_string(), name)),
+        }
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let Some((admin_url, name)) = self.cleanup.take() else {
+            return;
+        };
+        let sql = format!("DROP DATABASE IF EXISTS \"{name}\" WITH (FORCE)");
+        // The test's runtime may already be shutting down, so use a fresh one.
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("build runtime");
+            runtime.block_on(async {
+                if let Ok(admin) = Database::connect(admin_url).await {
+                    admin.execute_unprepared(&sql).await.ok();
+                    admin.close().await.ok();
+                }
+            });
+        })
+        .join()
+        .ok();
+    }
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+};
+use bloom_api::server::LEGACY_SUNSET;
+use common::{recipe_body, TestApp};
+use entity::sea_orm_active_enums::Role;
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn unversioned_paths_are_deprecated_aliases() {
+    let app = TestApp::spawn().await;
+    for (legacy, current) in [
+        ("/api/recipe/the-future", "/api/v1/recipes/slug/the-future"),
+        ("/api/recipe/id/1/tags", "/api/v1/recipes/1/tags"),
+        ("/api/roast/2", "/api/v1/roasts/2"),
+        ("/api/recipes/roaster/Onyx", "/api/v1/recipes/roaster/Onyx"),
+    ] {
+        let response = app.response(get(legacy)).await;
+        assert_eq!(response.status(), StatusCode::OK, "{legacy}");
+        let headers = response.headers();
+        assert_eq!(headers["deprecation"], "@1792195200");
+        assert_eq!(headers["sunset"], LEGACY_SUNSET);
+        assert!(headers[header::LINK]
+            .to_str()
+            .unwrap()
+            .starts_with("</api/v1/docs>; rel=\"deprecation\""));
+
+        let response = app.response(get(current)).await;
+        assert!(!response.headers().contains_key("deprecation"));
+        assert_eq!(app.get(legacy).await, app.get(current).await);
+    }
+}
+
+#[tokio::test]
+async fn unversioned_api_documentation_is_still_served() {
+    let app = TestApp::spawn().await;
+    for (legacy, current) in [
+        ("/api/openapi.json", "/api/v1/openapi.json"),
+        ("/api/docs", "/api/v1/docs"),
+    ] {
+        let response = app.response(get(legacy)).await;
+        assert_eq!(response.status(), StatusCode::OK, "{legacy}");
+        assert_eq!(response.headers()["sunset"], LEGACY_SUNSET);
+        assert_eq!(app.get(legacy).await, app.get(current).await);
+    }
+}
+
+#[tokio::test]
+async fn unversioned_writes_still_work() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("legacy", Role::Member).await;
+    let (status, created) = app
+        .json(
+            Method::POST,
+            "/api/recipe/create",
+            Some(&token),
+            &recipe_body("Old Client"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .form(
+            Method::PUT,
+            "/api/admin/brewer/1",
+            Some(&app.admin_token()),
+            "type=Renamed",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app
+        .delete(&format!("/api/recipe/id/{}", created["id"]), Some(&token))
+        .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.get(&format!("/api/v1/recipes/{}", created["id"])).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn unknown_paths_are_not_marked_deprecated() {
+    let app = TestApp::spawn().await;
+    let response = app.response(get("/api/nope")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(!response.headers().contains_key("deprecation"));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{items, TestApp};
+use serde_json::json;
+
+#[tokio::test]
+async fn root_reports_ok() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "status": "ok" }));
+}
+
+#[tokio::test]
+async fn unknown_route_is_a_json_404() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+}
+
+#[tokio::test]
+async fn malformed_path_and_query_values_are_json_400s() {
+    let app = TestApp::spawn().await;
+    for uri in [
+        "/api/v1/recipes/abc",
+        "/api/v1/brewers/1.5",
+        "/api/v1/recipes?limit=abc",
+        "/api/v1/recipes/search?brewer_id=x",
+        "/api/v1/tags?cursor=-",
+    ] {
+        let (status, body) = app.get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert_eq!(body["code"], "bad_request", "{uri}");
+        assert!(body["message"].as_str().is_some_and(|m| !m.is_empty()));
+    }
+}
+
+#[tokio::test]
+async fn out_of_range_pages_are_400s() {
+    let app = TestApp::spawn().await;
+    for uri in [
+        "/api/v1/brewers?page=18446744073709551615",
+        "/api/v1/roasts?page=9223372036854775807&per_page=2",
+        "/api/v1/recipes/search?page=18446744073709551615",
+        "/api/v1/recipes/search?roaster=Onyx&page=18446744073709551615",
+        "/api/v1/recipes/search?q=future&page=18446744073709551615",
+    ] {
+        let (status, body) = app.get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        assert_eq!(body["code"], "bad_request", "{uri}");
+    }
+
+    // The largest offset the databases accept is still an (empty) page.
+    let (status, body) = app
+        .get("/api/v1/roasts?page=9223372036854775807&per_page=1")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(items(&body).is_empty());
+}
+
+#[tokio::test]
+async fn roasts_are_seeded_and_paginated() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/roasts").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 6);
+    assert_eq!(items(&body)[0]["level"], "Light");
+
+    let (_, first) = app.get("/api/v1/roasts?limit=4").await;
+    assert_eq!(items(&first).len(), 4);
+    let cursor = first["next_cursor"].as_i64().expect("a second page");
+    let (_, second) = app
+        .get(&format!("/api/v1/roasts?limit=4&cursor={cursor}"))
+        .await;
+    assert_eq!(items(&second).len(), 2);
+    assert!(second["next_cursor"].is_null());
+
+    let (_, page) = app.get("/api/v1/roasts?page=2&per_page=4").await;
+    assert_eq!(items(&page), items(&second));
+}
+
+#[tokio::test]
+async fn single_roast_or_404() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/roasts/2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 2, "level": "Dark" }));
+
+    let (status, body) = app.get("/api/v1/roasts/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["message"], "roast not found");
+}
+
+#[tokio::test]
+async fn brewers_are_seeded() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/brewers").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 5);
+
+    let (status, body) = app.get("/api/v1/brewers/3").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 3, "type": "Aeropress" }));
+
+    let (status, _) = app.get("/api/v1/brewers/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn users_only_expose_id_and_username() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/users").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(items(&body), &vec![json!({ "id": 1, "username": "admin" })]);
+
+    let (status, body) = app.get("/api/v1/users/1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "id": 1, "username": "admin" }));
+
+    let (status, _) = app.get("/api/v1/users/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{recipe_body, TestApp};
+use entity::sea_orm_active_enums::Role;
+
+#[tokio::test]
+async fn metrics_cover_requests_database_and_recipes() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("counted", Role::Member).await;
+    app.get("/api/v1/roasts/1").await;
+    app.get("/api/v1/nope").await;
+    app.get("/api/v1/recipes/search?q=future").await;
+    app.get("/api/v1/recipes/search?roaster=Onyx").await;
+    let (status, _) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &recipe_body("Counted"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app.get("/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    let text = body.as_str().expect("plain text body");
+    for expected in [
+        r#"http_requests_total{method="GET",route="/api/v1/roasts/:id",status="200"}"#,
+        r#"http_requests_total{method="GET",route="unmatched",status="404"}"#,
+        r#"http_requests_total{method="POST",route="/api/v1/recipes",status="200"}"#,
+        r#"http_request_duration_seconds_bucket{method="GET",route="/api/v1/roasts/:id",status="200",le="0.005"}"#,
+        r#"db_query_duration_seconds_bucket{outcome="ok",le="0.1"}"#,
+        r#"db_pool_connections{state="idle"}"#,
+        "db_pool_max_connections",
+        "recipes_created_total",
+        r#"recipe_searches_total{mode="filter"}"#,
+    ] {
+        assert!(text.contains(expected), "missing {expected} in:\n{text}");
+    }
+    assert!(
+        text.contains(r#"recipe_searches_total{mode="full_text"}"#)
+            || text.contains(r#"recipe_searches_total{mode="substring"}"#)
+    );
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use axum::http::StatusCode;
+use bloom_api::openapi::ApiDoc;
+use bloom_api::server;
+use common::TestApp;
+use std::collections::BTreeSet;
+use utoipa::OpenApi;
+
+#[test]
+fn spec_describes_every_route() {
+    let routed: BTreeSet<_> = server::documented_routes()
+        .into_iter()
+        .map(|(method, path)| (method.as_str().to_lowercase(), openapi_path(path)))
+        .collect();
+    let documented = documented_operations();
+    let missing: Vec<_> = routed.difference(&documented).collect();
+    assert!(
+        missing.is_empty(),
+        "routes missing from the spec: {missing:?}"
+    );
+    let stale: Vec<_> = documented.difference(&routed).collect();
+    assert!(
+        stale.is_empty(),
+        "spec documents unrouted operations: {stale:?}"
+    );
+}
+
+#[tokio::test]
+async fn spec_and_docs_are_served() {
+    let app = TestApp::spawn().await;
+    let (status, spec) = app.get("/api/v1/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+    let schemas = &spec["components"]["schemas"];
+    for schema in [
+        "Recipe",
+        "RecipeDetail",
+        "CreateRecipe",
+        "PartialUser",
+        "ErrorBody",
+    ] {
+        assert!(schemas[schema].is_object(), "missing schema {schema}");
+    }
+    assert_eq!(
+        spec["components"]["securitySchemes"]["bearer"]["scheme"],
+        "bearer"
+    );
+    assert_eq!(
+        spec["paths"]["/api/v1/recipes"]["post"]["security"][0]["bearer"],
+        serde_json::json!([])
+    );
+
+    let (status, page) = app.get("/api/v1/docs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.as_str().unwrap().contains("/api/v1/openapi.json"));
+}
+
+/// Every `(method, path)` the spec describes.
+fn documented_operations() -> BTreeSet<(String, String)> {
+    ApiDoc::openapi()
+        .paths
+        .paths
+        .into_iter()
+        .flat_map(|(path, item)| {
+            [
+                ("get", item.get.is_some()),
+                ("post", item.post.is_some()),
+                ("put", item.put.is_some()),
+                ("patch", item.patch.is_some()),
+                ("delete", item.delete.is_some()),
+            ]
+            .into_iter()
+            .filter(|(_, documented)| *documented)
+            .map(move |(method, _)| (method.to_string(), path.clone()))
+        })
+        .collect()
+}
+
+/// Rewrites axum's `:param` segments to OpenAPI's `{param}`.
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(param) => format!("{{{param}}}"),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
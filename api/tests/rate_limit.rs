@@ -0,0 +1,181 @@
+mod common;
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, Method, Request, StatusCode},
+};
+use common::{recipe_body, TestApp};
+use entity::sea_orm_active_enums::Role;
+use std::net::SocketAddr;
+
+/// A GET of `uri` sent over a connection from `peer`.
+fn get_from(uri: &str, peer: [u8; 4]) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .extension(ConnectInfo(SocketAddr::from((peer, 40000))))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// A title search sent over a connection from `peer`, forwarded for `forwarded`.
+fn search_from(peer: [u8; 4], forwarded: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder()
+        .uri("/api/v1/recipes/title/Future")
+        .extension(ConnectInfo(SocketAddr::from((peer, 40000))));
+    if let Some(forwarded) = forwarded {
+        builder = builder.header("x-forwarded-for", forwarded);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn writes_are_limited_per_user() {
+    let app = TestApp::spawn_with(|config| config.rate_limit_writes_per_minute = 2).await;
+    let (_, alice) = app.user("alice", Role::Member).await;
+    let (_, bob) = app.user("bob", Role::Member).await;
+
+    for title in ["First", "Second"] {
+        let (status, _) = app
+            .json(
+                Method::POST,
+                "/api/v1/recipes",
+                Some(&alice),
+                &recipe_body(title),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/v1/recipes")
+        .header(header::AUTHORIZATION, format!("Bearer {alice}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(recipe_body("Third").to_string()))
+        .unwrap();
+    let response = app.response(request).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let headers = response.headers();
+    assert_eq!(headers["ratelimit-limit"], "2");
+    assert_eq!(headers["ratelimit-remaining"], "0");
+    assert_eq!(headers["ratelimit-policy"], "2;w=60");
+    let retry_after: u64 = headers[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=30).contains(&retry_after));
+
+    // Other users and read routes have their own budgets.
+    let (status, _) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&bob),
+            &recipe_body("Third"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.get("/api/v1/recipes/slug/first").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn deprecated_aliases_share_the_v1_budget() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit_writes_per_minute = 2;
+        config.rate_limit_searches_per_minute = 2;
+    })
+    .await;
+    let (_, token) = app.user("alias", Role::Member).await;
+
+    let writes = ["/api/v1/recipes", "/api/recipe/create", "/api/v1/recipes"];
+    for (uri, title) in writes.into_iter().zip(["First", "Second", "Third"]) {
+        let (status, _) = app
+            .json(Method::POST, uri, Some(&token), &recipe_body(title))
+            .await;
+        let expected = if title == "Third" {
+            StatusCode::TOO_MANY_REQUESTS
+        } else {
+            StatusCode::OK
+        };
+        assert_eq!(status, expected, "POST {uri}");
+    }
+
+    let searches = [
+        "/api/recipes/search?q=future",
+        "/api/v1/recipes/search?q=future",
+        "/api/recipes/search?q=future",
+    ];
+    for (i, uri) in searches.into_iter().enumerate() {
+        let (status, _) = app.send(get_from(uri, [192, 0, 2, 1])).await;
+        let expected = if i == 2 {
+            StatusCode::TOO_MANY_REQUESTS
+        } else {
+            StatusCode::OK
+        };
+        assert_eq!(status, expected, "GET {uri}");
+    }
+}
+
+#[tokio::test]
+async fn searches_are_limited_per_client_ip() {
+    let app = TestApp::spawn_with(|config| {
+        config.rate_limit_searches_per_minute = 1;
+        config.trusted_proxies = vec!["10.0.0.1".parse().unwrap()];
+    })
+    .await;
+
+    let response = app.response(search_from([192, 0, 2, 1], None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["ratelimit-remaining"], "0");
+    let (status, body) = app.send(search_from([192, 0, 2, 1], None)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "rate_limited");
+
+    // Behind the trusted proxy each forwarded client has its own bucket...
+    let (status, _) = app
+        .send(search_from([10, 0, 0, 1], Some("198.51.100.7")))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .send(search_from([10, 0, 0, 1], Some("198.51.100.8")))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app
+        .send(search_from([10, 0, 0, 1], Some("spoofed, 198.51.100.8")))
+        .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // ...but the header is ignored when an untrusted peer sends it.
+    let (status, _) = app
+        .send(search_from([192, 0, 2, 1], Some("198.51.100.9")))
+        .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn disabled_limiter_adds_no_headers() {
+    let app = TestApp::spawn_with(|config| config.rate_limit_enabled = false).await;
+    let response = app.response(search_from([192, 0, 2, 1], None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.headers().contains_key("ratelimit-limit"));
+}
+
+#[tokio::test]
+async fn anonymous_requests_without_a_client_address_are_not_limited() {
+    let app = TestApp::spawn_with(|config| config.rate_limit_searches_per_minute = 1).await;
+    for _ in 0..3 {
+        let response = app
+            .response(
+                Request::builder()
+                    .uri("/api/v1/recipes/title/Future")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("ratelimit-limit"));
+    }
+}
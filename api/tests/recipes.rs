@@ -0,0 +1,547 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::{NaiveDate, NaiveTime};
+use common::{items, recipe_body, titles, TestApp, SEEDED_RECIPE_ID};
+use entity::recipe as Recipe;
+use entity::sea_orm_active_enums::Role;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter};
+
+#[tokio::test]
+async fn seeded_recipe_is_listed() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/recipes").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 1);
+    let recipe = &items(&body)[0];
+    assert_eq!(recipe["title"], "The Future");
+    assert_eq!(recipe["slug"], "the-future");
+    assert_eq!(recipe["roaster"], "Black and White");
+}
+
+#[tokio::test]
+async fn seeded_recipe_by_id_slug_and_title() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app
+        .get(&format!("/api/v1/recipes/{SEEDED_RECIPE_ID}"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["title"], "The Future");
+
+    let (status, body) = app.get("/api/v1/recipes/slug/the-future").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], SEEDED_RECIPE_ID);
+
+    let (status, body) = app.get("/api/v1/recipes/title/Future").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&body), ["The Future"]);
+
+    let (status, _) = app.get("/api/v1/recipes/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app.get("/api/v1/recipes/slug/no-such-slug").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn recipes_by_roaster_and_machine() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app.get("/api/v1/recipes/roaster/Black%20and%20White").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&body), ["The Future"]);
+
+    let (status, body) = app.get("/api/v1/recipes/machine/Studio").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&body), ["The Future"]);
+
+    let (_, body) = app.get("/api/v1/recipes/roaster/Nobody").await;
+    assert!(items(&body).is_empty());
+}
+
+#[tokio::test]
+async fn expand_embeds_related_rows() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app
+        .get("/api/v1/recipes/slug/the-future?expand=brewer,roast,user,tags")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["brewer"]["type"], "Omni Dripper v2");
+    assert_eq!(body["roast"]["level"], "Light");
+    assert_eq!(body["user"]["username"], "admin");
+    assert!(body["tags"].as_array().unwrap().is_empty());
+
+    let (status, body) = app.get("/api/v1/recipes?expand=bogus").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "bad_request");
+}
+
+#[tokio::test]
+async fn search_filters_and_sorts() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("searcher", Role::Member).await;
+    let (status, _) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &recipe_body("Geometry Iced"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app.get("/api/v1/recipes/search?title=future").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&body), ["The Future"]);
+
+    let (_, body) = app.get("/api/v1/recipes/search?roaster=Onyx").await;
+    assert_eq!(titles(&body), ["Geometry Iced"]);
+
+    let (_, body) = app.get("/api/v1/recipes/search?creator=searcher").await;
+    assert_eq!(titles(&body), ["Geometry Iced"]);
+
+    backdate(&app, "The Future", 2020).await;
+    let (_, body) = app.get("/api/v1/recipes/search?sort=newest").await;
+    assert_eq!(titles(&body), ["Geometry Iced", "The Future"]);
+
+    let (_, body) = app.get("/api/v1/recipes/search?q=geometr*").await;
+    assert_eq!(titles(&body), ["Geometry Iced"]);
+
+    let (_, body) = app
+        .get("/api/v1/recipes/search?q=%22black%20and%20white%22")
+        .await;
+    assert_eq!(titles(&body), ["The Future"]);
+}
+
+#[tokio::test]
+async fn full_text_search_ranks_title_matches_first_and_pages_with_cursors() {
+    let app = TestApp::spawn().await;
+    let (_, tester) = app.user("tester", Role::Member).await;
+    let (_, iced) = app.user("iced", Role::Member).await;
+
+    // Created in the opposite order to their rank: a tag match, a creator match
+    // and a title match.
+    for (title, token) in [
+        ("Tagged Pour", &tester),
+        ("Plain Pour", &iced),
+        ("Iced Geometry", &tester),
+    ] {
+        let (status, created) = app
+            .json(
+                Method::POST,
+                "/api/v1/recipes",
+                Some(token),
+                &recipe_body(title),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        if title == "Tagged Pour" {
+            let uri = format!("/api/v1/recipes/{}/tags", created["id"]);
+            let (status, _) = app.form(Method::POST, &uri, Some(token), "tags=Iced").await;
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+
+    let expected = if app.conn.get_database_backend() == DbBackend::Postgres {
+        ["Iced Geometry", "Plain Pour", "Tagged Pour"]
+    } else {
+        // Substring matching keeps the creation order.
+        ["Tagged Pour", "Plain Pour", "Iced Geometry"]
+    };
+    let (status, body) = app.get("/api/v1/recipes/search?q=iced").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&body), expected);
+
+    let mut paged = Vec::new();
+    let mut uri = "/api/v1/recipes/search?q=iced&limit=1".to_string();
+    loop {
+        let (status, body) = app.get(&uri).await;
+        assert_eq!(status, StatusCode::OK);
+        paged.extend(titles(&body).into_iter().map(str::to_string));
+        match body["next_cursor"].as_i64() {
+            Some(cursor) => uri = format!("/api/v1/recipes/search?q=iced&limit=1&cursor={cursor}"),
+            None => break,
+        }
+        assert!(paged.len() <= expected.len(), "cursor loops: {paged:?}");
+    }
+    assert_eq!(paged, expected);
+}
+
+#[tokio::test]
+async fn search_title_wildcards_are_literal_and_sort_uses_creation_time() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("searcher", Role::Member).await;
+    for title in ["100% Arabica", "Backdated"] {
+        let (status, _) = app
+            .json(
+                Method::POST,
+                "/api/v1/recipes",
+                Some(&token),
+                &recipe_body(title),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, body) = app.get("/api/v1/recipes/search?title=%25").await;
+    assert_eq!(titles(&body), ["100% Arabica"]);
+    let (_, body) = app.get("/api/v1/recipes/search?title=_").await;
+    assert!(items(&body).is_empty());
+    let (_, body) = app.get("/api/v1/recipes/title/0%25%20a").await;
+    assert_eq!(titles(&body), ["100% Arabica"]);
+
+    // The newest id, but created before everything else.
+    backdate(&app, "Backdated", 2020).await;
+    backdate(&app, "The Future", 2021).await;
+    backdate(&app, "100% Arabica", 2022).await;
+    let (_, body) = app.get("/api/v1/recipes/search?sort=oldest").await;
+    assert_eq!(titles(&body), ["Backdated", "The Future", "100% Arabica"]);
+
+    let (_, body) = app.get("/api/v1/recipes/search?sort=newest&limit=1").await;
+    assert_eq!(titles(&body), ["100% Arabica"]);
+    let cursor = &body["next_cursor"];
+    let (_, body) = app
+        .get(&format!(
+            "/api/v1/recipes/search?sort=newest&limit=2&cursor={cursor}"
+        ))
+        .await;
+    assert_eq!(titles(&body), ["The Future", "Backdated"]);
+}
+
+#[tokio::test]
+async fn creating_requires_a_token() {
+    let app = TestApp::spawn().await;
+    let (status, body) = app
+        .json(Method::POST, "/api/v1/recipes", None, &recipe_body("Anon"))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+
+    let (status, _) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some("not-a-jwt"),
+            &recipe_body("Anon"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_from_json_or_form() {
+    let app = TestApp::spawn().await;
+    let (user, token) = app.user("brewer", Role::Member).await;
+
+    let (status, body) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &recipe_body("Morning Cup"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["slug"], "morning-cup");
+    assert_eq!(body["creator"], "brewer");
+    assert_eq!(body["user_id"], user.id);
+
+    let form = "title=Evening+Cup&roaster=Onyx&temp=iced\
+        &link=https%3A%2F%2Fshare-h5.xbloom.com%2F%3Fid%3Dxyz\
+        &shop_link=https%3A%2F%2Fexample.com&machine=Studio&brewer_id=2&roast_id=3";
+    let (status, body) = app
+        .form(Method::POST, "/api/v1/recipes", Some(&token), form)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["slug"], "evening-cup");
+    assert_eq!(body["temp"], "iced");
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_fields() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("sloppy", Role::Member).await;
+    let mut body = recipe_body("");
+    body["temp"] = "lukewarm".into();
+    body["link"] = "https://example.com/recipe".into();
+    body["brewer_id"] = 999.into();
+
+    let (status, body) = app
+        .json(Method::POST, "/api/v1/recipes", Some(&token), &body)
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "validation_failed");
+    let fields: Vec<&str> = body["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, ["title", "temp", "link", "brewer_id"]);
+}
+
+#[tokio::test]
+async fn create_rejects_malformed_bodies_and_duplicate_titles() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("dupe", Role::Member).await;
+
+    let (status, body) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &serde_json::json!({ "title": "Missing everything" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "unprocessable_entity");
+
+    let (status, body) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &recipe_body("The Future"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], "conflict");
+}
+
+#[tokio::test]
+async fn create_rejects_links_to_an_already_shared_recipe() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("dupe", Role::Member).await;
+
+    // Variants of the seeded recipe's link that still name the same xBloom recipe.
+    for link in [
+        "https://SHARE-H5.xbloom.com/?id=8yAUAWJktyHNIpo3vjZ6pA%3D%3D",
+        "https://share-h5.xbloom.com?utm_source=app&id=8yAUAWJktyHNIpo3vjZ6pA==#top",
+        " https://share-h5.xbloom.com/?id=8yAUAWJktyHNIpo3vjZ6pA== ",
+    ] {
+        let mut body = recipe_body("Future Again");
+        body["link"] = link.into();
+        let (status, body) = app
+            .json(Method::POST, "/api/v1/recipes", Some(&token), &body)
+            .await;
+        assert_eq!(status, StatusCode::CONFLICT, "{link}");
+        assert_eq!(body["code"], "duplicate_recipe");
+        assert_eq!(body["existing_slug"], "the-future");
+    }
+
+    let (status, created) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &recipe_body("Future Again"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(created["share_id"].is_string());
+
+    let mut update = recipe_body("Future Again");
+    update["link"] = "https://share-h5.xbloom.com/?id=8yAUAWJktyHNIpo3vjZ6pA==".into();
+    let uri = format!("/api/v1/recipes/{}", created["id"]);
+    let (status, body) = app.json(Method::PUT, &uri, Some(&token), &update).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["existing_slug"], "the-future");
+
+    // Re-saving a recipe with its own link is not a duplicate.
+    let (status, _) = app
+        .json(
+            Method::PUT,
+            &uri,
+            Some(&token),
+            &recipe_body("Future Again"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn concurrent_shares_of_one_recipe_report_the_duplicate() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("racer", Role::Member).await;
+
+    // Both requests pass the duplicate check before either is stored, so the
+    // loser is caught by the unique index on share_id.
+    let mut first = recipe_body("Race One");
+    let mut second = recipe_body("Race Two");
+    first["link"] = "https://share-h5.xbloom.com/?id=RaceShared".into();
+    second["link"] = first["link"].clone();
+    let (one, two) = tokio::join!(
+        app.json(Method::POST, "/api/v1/recipes", Some(&token), &first),
+        app.json(Method::POST, "/api/v1/recipes", Some(&token), &second),
+    );
+
+    let (created, rejected) = if one.0 == StatusCode::OK {
+        (one, two)
+    } else {
+        (two, one)
+    };
+    assert_eq!(created.0, StatusCode::OK);
+    assert_eq!(rejected.0, StatusCode::CONFLICT, "{}", rejected.1);
+    assert_eq!(rejected.1["code"], "duplicate_recipe");
+    assert_eq!(rejected.1["existing_slug"], created.1["slug"]);
+}
+
+#[tokio::test]
+async fn update_is_limited_to_owner_and_moderators() {
+    let app = TestApp::spawn().await;
+    let (_, owner) = app.user("owner", Role::Member).await;
+    let (_, stranger) = app.user("stranger", Role::Member).await;
+    let (_, moderator) = app.user("moderator", Role::Moderator).await;
+
+    let (_, created) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&owner),
+            &recipe_body("Original"),
+        )
+        .await;
+    let uri = format!("/api/v1/recipes/{}", created["id"]);
+
+    let (status, body) = app
+        .json(Method::PUT, &uri, Some(&owner), &recipe_body("Renamed"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["slug"], "renamed");
+    assert_eq!(body["creator"], "owner");
+
+    let (status, _) = app
+        .json(Method::PUT, &uri, Some(&stranger), &recipe_body("Hijacked"))
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = app
+        .json(
+            Method::PUT,
+            &uri,
+            Some(&moderator),
+            &recipe_body("Moderated"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["creator"], "owner");
+
+    let (status, _) = app
+        .json(
+            Method::PUT,
+            "/api/v1/recipes/999",
+            Some(&owner),
+            &recipe_body("Ghost"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn patch_changes_only_the_fields_sent() {
+    let app = TestApp::spawn().await;
+    let (_, owner) = app.user("owner", Role::Member).await;
+    let (_, stranger) = app.user("stranger", Role::Member).await;
+
+    let mut body = recipe_body("Original");
+    body["dose_grams"] = 15.0.into();
+    let (_, created) = app
+        .json(Method::POST, "/api/v1/recipes", Some(&owner), &body)
+        .await;
+    let uri = format!("/api/v1/recipes/{}", created["id"]);
+
+    let (status, body) = app
+        .json(
+            Method::PATCH,
+            &uri,
+            Some(&owner),
+            &serde_json::json!({ "roaster": "Sey" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["roaster"], "Sey");
+    assert_eq!(body["title"], "Original");
+    assert_eq!(body["link"], created["link"]);
+    assert_eq!(body["dose_grams"], 15.0);
+
+    let (status, body) = app
+        .form(Method::PATCH, &uri, Some(&owner), "title=Renamed&temp=iced")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["slug"], "renamed");
+    assert_eq!(body["temp"], "iced");
+    assert_eq!(body["roaster"], "Sey");
+
+    let (status, body) = app
+        .json(
+            Method::PATCH,
+            &uri,
+            Some(&owner),
+            &serde_json::json!({ "temp": "lukewarm" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["fields"][0]["field"], "temp");
+
+    let (status, _) = app
+        .json(
+            Method::PATCH,
+            &uri,
+            Some(&stranger),
+            &serde_json::json!({ "title": "Hijacked" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn delete_removes_recipe_and_its_tags() {
+    let app = TestApp::spawn().await;
+    let (_, owner) = app.user("owner", Role::Member).await;
+    let (_, stranger) = app.user("stranger", Role::Member).await;
+
+    let (_, created) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&owner),
+            &recipe_body("Short Lived"),
+        )
+        .await;
+    let uri = format!("/api/v1/recipes/{}", created["id"]);
+    let (status, _) = app
+        .form(
+            Method::POST,
+            &format!("{uri}/tags"),
+            Some(&owner),
+            "tags=temp",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = app.delete(&uri, Some(&stranger)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.delete(&uri, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = app.delete(&uri, Some(&owner)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(body.is_null());
+
+    let (status, _) = app.get(&uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, tags) = app.get("/api/v1/tags").await;
+    assert_eq!(items(&tags)[0]["recipes"], 0);
+}
+
+/// Moves the `created_at` of the recipe titled `title` to the start of `year`.
+async fn backdate(app: &TestApp, title: &str, year: i32) {
+    let created_at = NaiveDate::from_ymd_opt(year, 1, 1)
+        .unwrap()
+        .and_time(NaiveTime::MIN);
+    Recipe::Entity::update_many()
+        .col_expr(Recipe::Column::CreatedAt, Expr::value(created_at))
+        .filter(Recipe::Column::Title.eq(title))
+        .exec(&app.conn)
+        .await
+        .expect("backdate recipe");
+}
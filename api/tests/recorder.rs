@@ -0,0 +1,43 @@
+mod common;
+
+use axum::{body::Body, http::Request, http::StatusCode};
+use bloom_api::config::Config;
+use bloom_api::metrics;
+use bloom_api::server::{self, AppState};
+use common::TestApp;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+// Kept in its own test binary: the global recorder can only be installed once
+// per process, and this test has to install it before anything else does.
+#[tokio::test]
+async fn an_embedding_application_keeps_its_own_recorder() {
+    let handle = metrics::builder()
+        .install_recorder()
+        .expect("no recorder is installed yet");
+
+    let app = TestApp::spawn().await;
+    let (status, _) = app.get("/api/v1/roasts/1").await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.get("/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let router = server::app(AppState::with_metrics(
+        app.conn.clone(),
+        Config::default(),
+        handle,
+    ));
+    let response = router
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(
+        text.contains(
+            r#"http_requests_total{method="GET",route="/api/v1/roasts/:id",status="200"}"#
+        ),
+        "{text}"
+    );
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{items, recipe_body, TestApp};
+use entity::sea_orm_active_enums::Role;
+use serde_json::{json, Value};
+
+/// A recipe body with brew parameters and two pours.
+fn brewed_recipe_body(title: &str) -> Value {
+    let mut body = recipe_body(title);
+    body["dose_grams"] = json!(15.0);
+    body["grind_size"] = json!(52);
+    body["ratio"] = json!(16.0);
+    body["steps"] = json!([
+        {"volume_ml": 50, "temperature_c": 93, "flow_rate": 3.0, "pattern": "spiral", "pause_secs": 30},
+        {"volume_ml": 190, "temperature_c": 92, "flow_rate": 3.5, "pattern": "circular"},
+    ]);
+    body
+}
+
+fn volumes(recipe: &Value) -> Vec<i64> {
+    recipe["steps"]
+        .as_array()
+        .expect("recipe has steps")
+        .iter()
+        .map(|step| step["volume_ml"].as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn steps_are_returned_with_the_recipe() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("brewer", Role::Member).await;
+    let (status, created) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &brewed_recipe_body("Two Pours"),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["dose_grams"], 15.0);
+    assert_eq!(created["grind_size"], 52);
+    assert_eq!(created["ratio"], 16.0);
+    assert_eq!(volumes(&created), [50, 190]);
+    assert_eq!(created["steps"][1]["position"], 1);
+    assert_eq!(created["steps"][1]["pause_secs"], 0);
+
+    let (status, body) = app.get("/api/v1/recipes/slug/two-pours").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["steps"], created["steps"]);
+    assert_eq!(body["steps"][0]["pattern"], "spiral");
+
+    let (_, body) = app.get("/api/v1/recipes/roaster/Onyx").await;
+    assert!(items(&body)[0].get("steps").is_none());
+    let (_, body) = app.get("/api/v1/recipes/roaster/Onyx?expand=steps").await;
+    assert_eq!(volumes(&items(&body)[0]), [50, 190]);
+}
+
+#[tokio::test]
+async fn update_replaces_steps_only_when_sent() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("brewer", Role::Member).await;
+    let (_, created) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&token),
+            &brewed_recipe_body("Two Pours"),
+        )
+        .await;
+    let uri = format!("/api/v1/recipes/{}", created["id"]);
+
+    let (status, body) = app
+        .json(Method::PUT, &uri, Some(&token), &recipe_body("Two Pours"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(volumes(&body), [50, 190]);
+    assert!(body["dose_grams"].is_null());
+
+    let mut update = brewed_recipe_body("Two Pours");
+    update["steps"] = json!([
+        {"volume_ml": 240, "temperature_c": 90, "flow_rate": 3.0, "pattern": "center"},
+    ]);
+    let (status, body) = app.json(Method::PUT, &uri, Some(&token), &update).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(volumes(&body), [240]);
+
+    let (status, _) = app.delete(&uri, Some(&token)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn invalid_steps_are_rejected() {
+    let app = TestApp::spawn().await;
+    let (_, token) = app.user("brewer", Role::Member).await;
+    let mut body = brewed_recipe_body("Bad Pours");
+    body["ratio"] = json!(0.5);
+    body["steps"][1]["pattern"] = json!("zigzag");
+    body["steps"][1]["temperature_c"] = json!(120);
+    let (status, body) = app
+        .json(Method::POST, "/api/v1/recipes", Some(&token), &body)
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = body["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        fields,
+        ["ratio", "steps[1].temperature_c", "steps[1].pattern"]
+    );
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{items, recipe_body, titles, TestApp, SEEDED_RECIPE_ID};
+use entity::sea_orm_active_enums::Role;
+use entity::tag as Tag;
+use entity::tag_recipe as TagRecipe;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ActiveModelTrait, ActiveValue};
+use serde_json::json;
+
+#[tokio::test]
+async fn tagging_a_recipe() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token();
+    let uri = format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags");
+
+    let (status, body) = app
+        .form(Method::POST, &uri, Some(&admin), "tags=Fruity,+light+body")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!([{ "id": 1, "title": "Fruity" }, { "id": 2, "title": "light body" }])
+    );
+
+    // Tags are matched case-insensitively and attaching twice is a no-op.
+    let (status, body) = app
+        .form(Method::POST, &uri, Some(&admin), "tags=fruity")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let (status, body) = app.get(&uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+
+    let (status, body) = app.get("/api/v1/tags").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        items(&body),
+        &vec![
+            json!({ "id": 1, "title": "Fruity", "recipes": 1 }),
+            json!({ "id": 2, "title": "light body", "recipes": 1 }),
+        ]
+    );
+
+    let (status, body) = app.get("/api/v1/recipes/tag/FRUITY").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&body), ["The Future"]);
+
+    let (_, body) = app
+        .get("/api/v1/recipes/search?tags=fruity,light%20body")
+        .await;
+    assert_eq!(titles(&body), ["The Future"]);
+    let (_, body) = app.get("/api/v1/recipes/search?tags=fruity,nutty").await;
+    assert!(items(&body).is_empty());
+}
+
+#[tokio::test]
+async fn removing_a_tag() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token();
+    let uri = format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags");
+    app.form(Method::POST, &uri, Some(&admin), "tags=nutty")
+        .await;
+
+    let (status, _) = app.delete(&format!("{uri}/1"), Some(&admin)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = app.delete(&format!("{uri}/1"), Some(&admin)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = app.get(&uri).await;
+    assert_eq!(body, json!([]));
+}
+
+#[tokio::test]
+async fn only_owners_tag_recipes() {
+    let app = TestApp::spawn().await;
+    let (_, member) = app.user("member", Role::Member).await;
+    let uri = format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags");
+
+    let (status, _) = app.form(Method::POST, &uri, None, "tags=x").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.form(Method::POST, &uri, Some(&member), "tags=x").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (_, created) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&member),
+            &recipe_body("Mine"),
+        )
+        .await;
+    let (status, _) = app
+        .form(
+            Method::POST,
+            &format!("/api/v1/recipes/{}/tags", created["id"]),
+            Some(&member),
+            "tags=mine",
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn tags_of_a_missing_recipe() {
+    let app = TestApp::spawn().await;
+    let (status, _) = app.get("/api/v1/recipes/999/tags").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = app
+        .form(
+            Method::POST,
+            "/api/v1/recipes/999/tags",
+            Some(&app.admin_token()),
+            "tags=x",
+        )
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn concurrent_tagging_creates_one_tag_per_title() {
+    let app = TestApp::spawn().await;
+    let admin = app.admin_token();
+    let uri = format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags");
+
+    let (first, second) = tokio::join!(
+        app.form(Method::POST, &uri, Some(&admin), "tags=Iced"),
+        app.form(Method::POST, &uri, Some(&admin), "tags=iced"),
+    );
+    assert_eq!(first.0, StatusCode::OK);
+    assert_eq!(second.0, StatusCode::OK);
+
+    let (_, body) = app.get("/api/v1/tags").await;
+    assert_eq!(items(&body).len(), 1, "{body}");
+    assert_eq!(items(&body)[0]["recipes"], 1);
+
+    // The index backs the admin routes too.
+    let (status, _) = app
+        .json(
+            Method::POST,
+            "/api/v1/admin/tags",
+            Some(&admin),
+            &json!({ "title": "ICED" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn migration_merges_tags_that_differ_only_in_case() {
+    let app = TestApp::spawn().await;
+    let (_, member) = app.user("member", Role::Member).await;
+    let (_, other) = app
+        .json(
+            Method::POST,
+            "/api/v1/recipes",
+            Some(&member),
+            &recipe_body("Other"),
+        )
+        .await;
+    let other = other["id"].as_i64().unwrap() as i32;
+
+    // Undo the unique index to recreate data written before it existed.
+    Migrator::down(&app.conn, Some(1)).await.unwrap();
+    for title in ["Iced", "ICED", "iced"] {
+        Tag::ActiveModel {
+            title: ActiveValue::set(title.to_string()),
+            ..Default::default()
+        }
+        .insert(&app.conn)
+        .await
+        .unwrap();
+    }
+    for (tag_id, recipe_id) in [(2, SEEDED_RECIPE_ID), (3, SEEDED_RECIPE_ID), (2, other)] {
+        TagRecipe::ActiveModel {
+            tag_id: ActiveValue::set(tag_id),
+            recipe_id: ActiveValue::set(recipe_id),
+            ..Default::default()
+        }
+        .insert(&app.conn)
+        .await
+        .unwrap();
+    }
+    Migrator::up(&app.conn, None).await.unwrap();
+
+    let (_, body) = app.get("/api/v1/tags").await;
+    assert_eq!(
+        items(&body),
+        &vec![json!({ "id": 1, "title": "Iced", "recipes": 2 })]
+    );
+    let (_, body) = app
+        .get(&format!("/api/v1/recipes/{SEEDED_RECIPE_ID}/tags"))
+        .await;
+    assert_eq!(body, json!([{ "id": 1, "title": "Iced" }]));
+}
@@ -4,7 +4,9 @@ pub mod prelude;
 
 pub mod brewer;
 pub mod recipe;
+pub mod recipe_step;
 pub mod roast;
+pub mod sea_orm_active_enums;
 pub mod tag;
 pub mod tag_recipe;
 pub mod user;
@@ -2,6 +2,7 @@
 
 pub use super::brewer::Entity as Brewer;
 pub use super::recipe::Entity as Recipe;
+pub use super::recipe_step::Entity as RecipeStep;
 pub use super::roast::Entity as Roast;
 pub use super::tag::Entity as Tag;
 pub use super::tag_recipe::Entity as TagRecipe;
@@ -2,16 +2,18 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
 #[sea_orm(table_name = "recipe")]
+#[schema(as = Recipe)]
 pub struct Model {
     #[sea_orm(primary_key)]
-    #[serde(skip_deserializing)] // Skip deserializing
+    #[schema(read_only)]
     pub id: i32,
     #[sea_orm(unique)]
     pub title: String,
-    #[serde(skip_deserializing)] // Skip deserializing
+    #[schema(read_only)]
     #[sea_orm(unique)]
     pub slug: String,
     pub roaster: String,
@@ -20,11 +22,26 @@ pub struct Model {
     pub shop_link: String,
     pub machine: String,
     pub creator: String,
-    pub oauth_user: String,
+    pub oauth_user: Option<String>,
     pub user_id: i32,
     pub brewer_id: i32,
     pub roast_id: i32,
     pub created_at: DateTime,
+    /// Coffee dose, in grams.
+    #[serde(default)]
+    #[sea_orm(column_type = "Double", nullable)]
+    pub dose_grams: Option<f64>,
+    /// Grinder setting on the xBloom's built-in grinder.
+    #[serde(default)]
+    pub grind_size: Option<i32>,
+    /// Water to coffee ratio, e.g. `15.0` for 1:15.
+    #[serde(default)]
+    #[sea_orm(column_type = "Double", nullable)]
+    pub ratio: Option<f64>,
+    /// Canonical id of the xBloom share `link`, unique across recipes.
+    #[schema(read_only)]
+    #[sea_orm(unique)]
+    pub share_id: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -45,6 +62,8 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Roast,
+    #[sea_orm(has_many = "super::recipe_step::Entity")]
+    RecipeStep,
     #[sea_orm(has_many = "super::tag_recipe::Entity")]
     TagRecipe,
     #[sea_orm(
@@ -69,12 +88,27 @@ impl Related<super::roast::Entity> for Entity {
     }
 }
 
+impl Related<super::recipe_step::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecipeStep.def()
+    }
+}
+
 impl Related<super::tag_recipe::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::TagRecipe.def()
     }
 }
 
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::tag_recipe::Relation::Tag.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::tag_recipe::Relation::Recipe.def().rev())
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
@@ -2,11 +2,17 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ToSchema)]
 #[sea_orm(table_name = "roast")]
+#[schema(as = Roast)]
 pub struct Model {
+    /// Assigned by the database. Admin request bodies may omit it; `create_roast`
+    /// and `update_roast` never read it.
     #[sea_orm(primary_key)]
+    #[serde(default)]
+    #[schema(read_only)]
     pub id: i32,
     pub level: String,
 }
@@ -2,11 +2,17 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ToSchema)]
 #[sea_orm(table_name = "tag")]
+#[schema(as = Tag)]
 pub struct Model {
+    /// Assigned by the database. Admin request bodies may omit it; `create_tag`
+    /// and `update_tag` never read it.
     #[sea_orm(primary_key)]
+    #[serde(default)]
+    #[schema(read_only)]
     pub id: i32,
     pub title: String,
 }
@@ -17,6 +23,15 @@ pub enum Relation {
     TagRecipe,
 }
 
+impl Related<super::recipe::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::tag_recipe::Relation::Recipe.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::tag_recipe::Relation::Tag.def().rev())
+    }
+}
+
 impl Related<super::tag_recipe::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::TagRecipe.def()
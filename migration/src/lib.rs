@@ -7,6 +7,12 @@ mod m20240918_170443_create_tags;
 mod m20240918_170716_create_recipes;
 mod m20240918_182205_create_tags_recipes;
 mod m20240929_184147_alter_recipe_user;
+mod m20241005_090000_add_user_role;
+mod m20241008_100000_add_recipe_search;
+mod m20241010_120000_unique_tag_recipe;
+mod m20241012_090000_create_recipe_steps;
+mod m20241014_090000_add_recipe_share_id;
+mod m20241016_090000_unique_tag_title;
 
 pub struct Migrator;
 
@@ -21,6 +27,12 @@ impl MigratorTrait for Migrator {
             Box::new(m20240918_170716_create_recipes::Migration),
             Box::new(m20240918_182205_create_tags_recipes::Migration),
             Box::new(m20240929_184147_alter_recipe_user::Migration),
+            Box::new(m20241005_090000_add_user_role::Migration),
+            Box::new(m20241008_100000_add_recipe_search::Migration),
+            Box::new(m20241010_120000_unique_tag_recipe::Migration),
+            Box::new(m20241012_090000_create_recipe_steps::Migration),
+            Box::new(m20241014_090000_add_recipe_share_id::Migration),
+            Box::new(m20241016_090000_unique_tag_title::Migration),
         ]
     }
 }
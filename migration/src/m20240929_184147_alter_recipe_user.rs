@@ -1,6 +1,4 @@
-use super::m20240918_170716_create_recipes;
-use crate::sea_orm::sqlx::query_as_with;
-use sea_orm_migration::{prelude::*, schema::*};
+use sea_orm_migration::prelude::*;
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -10,7 +8,7 @@ impl MigrationTrait for Migration {
         manager
             .alter_table(
                 Table::alter()
-                    .table(m20240918_170716_create_recipes::Recipe::Table)
+                    .table(Recipe::Table)
                     .add_column_if_not_exists(ColumnDef::new(Recipe::OauthUser).string())
                     .to_owned(),
             )
@@ -20,7 +18,7 @@ impl MigrationTrait for Migration {
         manager
             .alter_table(
                 Table::alter()
-                    .table(m20240918_170716_create_recipes::Recipe::Table)
+                    .table(Recipe::Table)
                     .drop_column(Recipe::OauthUser)
                     .to_owned(),
             )
@@ -0,0 +1,45 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20240918_162751_create_users::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(string(UserRole::Role).default("member"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // The user seeded by the create_users migration is the site admin.
+        let update = Query::update()
+            .table(User::Table)
+            .value(UserRole::Role, "admin")
+            .and_where(Expr::col(User::Username).eq("admin"))
+            .to_owned();
+        manager.exec_stmt(update).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(UserRole::Role)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserRole {
+    Role,
+}
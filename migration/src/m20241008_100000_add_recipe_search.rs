@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DatabaseBackend;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Adds a weighted `tsvector` column over a recipe's title, roaster, creator and
+/// tag titles, plus a GIN index for full text search.
+///
+/// A `GENERATED` column cannot read the tag tables, so the column is kept up to
+/// date by triggers on `recipe`, `tag_recipe` and `tag` instead.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE recipe ADD COLUMN IF NOT EXISTS search tsvector;
+
+            CREATE OR REPLACE FUNCTION recipe_search_vector(r recipe) RETURNS tsvector AS $$
+                SELECT setweight(to_tsvector('simple', coalesce(r.title, '')), 'A')
+                    || setweight(to_tsvector('simple', coalesce(r.roaster, '')), 'B')
+                    || setweight(to_tsvector('simple', coalesce(r.creator, '')), 'B')
+                    || setweight(to_tsvector('simple', coalesce((
+                        SELECT string_agg(t.title, ' ')
+                        FROM tag_recipe tr JOIN tag t ON t.id = tr.tag_id
+                        WHERE tr.recipe_id = r.id
+                    ), '')), 'C')
+            $$ LANGUAGE sql STABLE;
+
+            CREATE OR REPLACE FUNCTION recipe_search_on_recipe() RETURNS trigger AS $$
+            BEGIN
+                NEW.search := recipe_search_vector(NEW);
+                RETURN NEW;
+            END
+            $$ LANGUAGE plpgsql;
+
+            CREATE OR REPLACE FUNCTION recipe_search_on_tag_recipe() RETURNS trigger AS $$
+            BEGIN
+                UPDATE recipe SET search = recipe_search_vector(recipe)
+                WHERE id = CASE WHEN TG_OP = 'DELETE' THEN OLD.recipe_id ELSE NEW.recipe_id END;
+                RETURN NULL;
+            END
+            $$ LANGUAGE plpgsql;
+
+            CREATE OR REPLACE FUNCTION recipe_search_on_tag() RETURNS trigger AS $$
+            BEGIN
+                UPDATE recipe SET search = recipe_search_vector(recipe)
+                WHERE id IN (SELECT recipe_id FROM tag_recipe WHERE tag_id = NEW.id);
+                RETURN NULL;
+            END
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER recipe_search_recipe
+                BEFORE INSERT OR UPDATE OF title, roaster, creator ON recipe
+                FOR EACH ROW EXECUTE FUNCTION recipe_search_on_recipe();
+
+            CREATE TRIGGER recipe_search_tag_recipe
+                AFTER INSERT OR DELETE ON tag_recipe
+                FOR EACH ROW EXECUTE FUNCTION recipe_search_on_tag_recipe();
+
+            CREATE TRIGGER recipe_search_tag
+                AFTER UPDATE OF title ON tag
+                FOR EACH ROW EXECUTE FUNCTION recipe_search_on_tag();
+
+            UPDATE recipe SET search = recipe_search_vector(recipe);
+
+            CREATE INDEX IF NOT EXISTS idx_recipe_search ON recipe USING GIN (search);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DatabaseBackend::Postgres {
+            return Ok(());
+        }
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            DROP TRIGGER IF EXISTS recipe_search_tag ON tag;
+            DROP TRIGGER IF EXISTS recipe_search_tag_recipe ON tag_recipe;
+            DROP TRIGGER IF EXISTS recipe_search_recipe ON recipe;
+            DROP FUNCTION IF EXISTS recipe_search_on_tag();
+            DROP FUNCTION IF EXISTS recipe_search_on_tag_recipe();
+            DROP FUNCTION IF EXISTS recipe_search_on_recipe();
+            DROP INDEX IF EXISTS idx_recipe_search;
+            ALTER TABLE recipe DROP COLUMN IF EXISTS search;
+            DROP FUNCTION IF EXISTS recipe_search_vector(recipe);
+            "#,
+        )
+        .await?;
+        Ok(())
+    }
+}
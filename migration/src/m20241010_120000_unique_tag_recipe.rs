@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Makes each tag attach to a recipe at most once.
+///
+/// Duplicate links that already exist are removed first, keeping the oldest
+/// row of each `(tag_id, recipe_id)` pair, so the index can be created.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(TagRecipe::Table)
+                    .and_where(
+                        Expr::col(TagRecipe::Id).not_in_subquery(
+                            Query::select()
+                                .expr(Expr::col(TagRecipe::Id).min())
+                                .from(TagRecipe::Table)
+                                .group_by_columns([TagRecipe::TagId, TagRecipe::RecipeId])
+                                .to_owned(),
+                        ),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tag_recipe_tag_id_recipe_id")
+                    .table(TagRecipe::Table)
+                    .col(TagRecipe::TagId)
+                    .col(TagRecipe::RecipeId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_tag_recipe_tag_id_recipe_id")
+                    .table(TagRecipe::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TagRecipe {
+    Table,
+    Id,
+    TagId,
+    RecipeId,
+}
@@ -0,0 +1,109 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20240918_170716_create_recipes::Recipe;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Adds the brew parameters to `recipe` and a `recipe_step` table holding each
+/// recipe's pours in order.
+///
+/// The new `recipe` columns are nullable because recipes shared before this
+/// migration only have a link to the xBloom app.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only accepts one column per ALTER TABLE.
+        for column in [
+            double_null(RecipeBrew::DoseGrams),
+            integer_null(RecipeBrew::GrindSize),
+            double_null(RecipeBrew::Ratio),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Recipe::Table)
+                        .add_column_if_not_exists(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecipeStep::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RecipeStep::Id))
+                    .col(integer(RecipeStep::RecipeId))
+                    .col(integer(RecipeStep::Position))
+                    .col(integer(RecipeStep::VolumeMl))
+                    .col(integer(RecipeStep::TemperatureC))
+                    .col(double(RecipeStep::FlowRate))
+                    .col(string(RecipeStep::Pattern))
+                    .col(integer(RecipeStep::PauseSecs).default(0))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_recipestep_recipe_id")
+                            .from(RecipeStep::Table, RecipeStep::RecipeId)
+                            .to(Recipe::Table, Recipe::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_recipe_step_recipe_id_position")
+                    .table(RecipeStep::Table)
+                    .col(RecipeStep::RecipeId)
+                    .col(RecipeStep::Position)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RecipeStep::Table).to_owned())
+            .await?;
+        for column in [
+            RecipeBrew::DoseGrams,
+            RecipeBrew::GrindSize,
+            RecipeBrew::Ratio,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Recipe::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum RecipeBrew {
+    DoseGrams,
+    GrindSize,
+    Ratio,
+}
+
+#[derive(DeriveIden)]
+enum RecipeStep {
+    Table,
+    Id,
+    RecipeId,
+    Position,
+    VolumeMl,
+    TemperatureC,
+    FlowRate,
+    Pattern,
+    PauseSecs,
+}
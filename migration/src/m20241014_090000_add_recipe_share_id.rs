@@ -0,0 +1,109 @@
+use sea_orm_migration::sea_orm::ConnectionTrait;
+use sea_orm_migration::{prelude::*, schema::*};
+use std::collections::HashSet;
+use url::Url;
+
+use super::m20240918_170716_create_recipes::Recipe;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Adds a unique `share_id` column to `recipe` holding the canonical id of the
+/// recipe's xBloom share link, so the same xBloom recipe cannot be shared twice.
+///
+/// Existing recipes are backfilled from their `link`. When several recipes
+/// already share an id, only the oldest gets it and the others keep a `NULL`.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Recipe::Table)
+                    .add_column_if_not_exists(string_null(RecipeShare::ShareId))
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let rows = db
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .columns([Recipe::Id, Recipe::Link])
+                        .from(Recipe::Table)
+                        .order_by(Recipe::Id, Order::Asc),
+                ),
+            )
+            .await?;
+        let mut seen = HashSet::new();
+        for row in rows {
+            let id: i32 = row.try_get("", &Recipe::Id.to_string())?;
+            let link: String = row.try_get("", &Recipe::Link.to_string())?;
+            let Some(share_id) = share_id(&link) else {
+                continue;
+            };
+            if !seen.insert(share_id.clone()) {
+                continue;
+            }
+            db.execute(
+                backend.build(
+                    Query::update()
+                        .table(Recipe::Table)
+                        .value(RecipeShare::ShareId, share_id)
+                        .and_where(Expr::col(Recipe::Id).eq(id)),
+                ),
+            )
+            .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_recipe_share_id")
+                    .table(Recipe::Table)
+                    .col(RecipeShare::ShareId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_recipe_share_id")
+                    .table(Recipe::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Recipe::Table)
+                    .drop_column(RecipeShare::ShareId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// A copy of the API's `xbloom::share_id` as it was when this migration was
+/// written, so later changes to the parser do not change what the backfill did.
+fn share_id(link: &str) -> Option<String> {
+    let url = Url::parse(link.trim()).ok()?;
+    if url.scheme() != "https" || url.host_str() != Some("share-h5.xbloom.com") {
+        return None;
+    }
+    url.query_pairs()
+        .filter(|(key, _)| key == "id")
+        .map(|(_, value)| value.trim().replace(' ', "+"))
+        .find(|id| !id.is_empty())
+}
+
+#[derive(DeriveIden)]
+enum RecipeShare {
+    ShareId,
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Makes tag titles unique ignoring case, so concurrent requests cannot create
+/// both `Iced` and `iced`.
+///
+/// Tags that already differ only in case are merged into the oldest of them
+/// first: their recipe links move to the kept tag, links the kept tag already
+/// has are dropped, and the duplicate tags are deleted.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = manager.get_database_backend();
+        let rows = db
+            .query_all(
+                backend.build(
+                    Query::select()
+                        .column(Tag::Id)
+                        .expr_as(Func::lower(Expr::col(Tag::Title)), Alias::new("key"))
+                        .from(Tag::Table)
+                        .order_by(Tag::Id, Order::Asc),
+                ),
+            )
+            .await?;
+
+        let mut kept: Vec<(String, i32)> = Vec::new();
+        for row in rows {
+            let id: i32 = row.try_get("", &Tag::Id.to_string())?;
+            let key: String = row.try_get("", "key")?;
+            let Some(&(_, keep)) = kept.iter().find(|(kept_key, _)| *kept_key == key) else {
+                kept.push((key, id));
+                continue;
+            };
+            merge_tag(manager, id, keep).await?;
+        }
+
+        // The SQLite index builder cannot express `lower(title)`, so the
+        // statement is written out; it reads the same on both backends.
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_tag_title_lower ON tag (lower(title))",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_tag_title_lower")
+                    .table(Tag::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// Moves the recipe links of tag `duplicate` to tag `keep` and deletes `duplicate`.
+async fn merge_tag(manager: &SchemaManager<'_>, duplicate: i32, keep: i32) -> Result<(), DbErr> {
+    manager
+        .exec_stmt(
+            Query::delete()
+                .from_table(TagRecipe::Table)
+                .and_where(Expr::col(TagRecipe::TagId).eq(duplicate))
+                .and_where(
+                    Expr::col(TagRecipe::RecipeId).in_subquery(
+                        Query::select()
+                            .column(TagRecipe::RecipeId)
+                            .from(TagRecipe::Table)
+                            .and_where(Expr::col(TagRecipe::TagId).eq(keep))
+                            .to_owned(),
+                    ),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .exec_stmt(
+            Query::update()
+                .table(TagRecipe::Table)
+                .value(TagRecipe::TagId, keep)
+                .and_where(Expr::col(TagRecipe::TagId).eq(duplicate))
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .exec_stmt(
+            Query::delete()
+                .from_table(Tag::Table)
+                .and_where(Expr::col(Tag::Id).eq(duplicate))
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+enum Tag {
+    Table,
+    Id,
+    Title,
+}
+
+#[derive(DeriveIden)]
+enum TagRecipe {
+    Table,
+    TagId,
+    RecipeId,
+}